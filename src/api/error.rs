@@ -0,0 +1,64 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Everything that can go wrong talking to the backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// Missing or invalid client configuration
+    Config(String),
+    /// No usable credentials, the user has to log in
+    Unauthenticated(String),
+    /// 4xx response
+    Client { status: u16, message: String },
+    /// 5xx response
+    Server { status: u16, message: String },
+    /// The request did not complete in time
+    Timeout,
+    /// Could not reach the server
+    Network(String),
+    /// The response body is not what we expected
+    Decode(String),
+    /// An argument that can't be sent, e.g. an empty id
+    InvalidArgument(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ApiError::Client { status, .. } | ApiError::Server { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Config(message) => write!(f, "API misconfigured: {}", message),
+            ApiError::Unauthenticated(message) => write!(f, "Not authenticated: {}", message),
+            ApiError::Client { status, message } => {
+                write!(f, "Request rejected ({}): {}", status, message)
+            }
+            ApiError::Server { status, message } => {
+                write!(f, "Server error ({}): {}", status, message)
+            }
+            ApiError::Timeout => write!(f, "Request timed out"),
+            ApiError::Network(message) => write!(f, "Network error: {}", message),
+            ApiError::Decode(message) => write!(f, "Invalid response: {}", message),
+            ApiError::InvalidArgument(message) => write!(f, "Invalid argument: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            ApiError::Timeout
+        } else if err.is_decode() {
+            ApiError::Decode(err.to_string())
+        } else {
+            ApiError::Network(err.to_string())
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::{str::FromStr, time::Duration};
+
+use log::warn;
+use reqwest::{Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use self::{
+    error::ApiError,
+    models::Item,
+    retry::{RetryNotice, RetryNotifier, RetryPolicy},
+};
+
+pub mod error;
+pub mod models;
+pub mod retry;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Backend settings, usually read from the environment (or `.env`)
+#[derive(Debug, Clone)]
+pub struct ApiConfig {
+    pub base_url: Url,
+    pub timeout: Duration,
+    pub retry: RetryPolicy,
+}
+
+impl ApiConfig {
+    /// Read `CHURRO_API_URL` (required), `CHURRO_API_TIMEOUT` (seconds),
+    /// `CHURRO_API_MAX_ATTEMPTS`, `CHURRO_API_BACKOFF_MS` and
+    /// `CHURRO_API_MAX_BACKOFF_MS` (all optional)
+    pub fn from_env() -> Result<Self, ApiError> {
+        let base_url = std::env::var("CHURRO_API_URL")
+            .map_err(|_| ApiError::Config(String::from("CHURRO_API_URL is not set")))?;
+        let base_url = Url::parse(&base_url)
+            .map_err(|err| ApiError::Config(format!("Invalid CHURRO_API_URL: {}", err)))?;
+        Self::for_url(base_url)
+    }
+
+    /// Talk to `base_url`, the rest is read from the environment like `from_env`
+    pub fn for_url(base_url: Url) -> Result<Self, ApiError> {
+        let timeout = env_var("CHURRO_API_TIMEOUT")?
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_TIMEOUT);
+
+        let default = RetryPolicy::default();
+        let retry = RetryPolicy {
+            max_attempts: env_var("CHURRO_API_MAX_ATTEMPTS")?
+                .unwrap_or(default.max_attempts)
+                .max(1),
+            base_delay: env_var("CHURRO_API_BACKOFF_MS")?
+                .map(Duration::from_millis)
+                .unwrap_or(default.base_delay),
+            max_delay: env_var("CHURRO_API_MAX_BACKOFF_MS")?
+                .map(Duration::from_millis)
+                .unwrap_or(default.max_delay),
+        };
+
+        Ok(Self {
+            base_url,
+            timeout,
+            retry,
+        })
+    }
+}
+
+fn env_var<T: FromStr>(name: &str) -> Result<Option<T>, ApiError> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| ApiError::Config(format!("Invalid {}: {}", name, value))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Typed client for the backend API
+#[derive(Clone)]
+pub struct ApiClient {
+    config: ApiConfig,
+    http: reqwest::Client,
+    on_retry: Option<RetryNotifier>,
+}
+
+impl ApiClient {
+    pub fn new(config: ApiConfig) -> Result<Self, ApiError> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|err| ApiError::Config(err.to_string()))?;
+        Ok(Self {
+            config,
+            http,
+            on_retry: None,
+        })
+    }
+
+    pub fn config(&self) -> &ApiConfig {
+        &self.config
+    }
+
+    /// Be told about each retry, e.g. to let the user know why it's slow
+    pub fn on_retry(mut self, notifier: RetryNotifier) -> Self {
+        self.on_retry = Some(notifier);
+        self
+    }
+
+    pub async fn items(&self, token: Option<&str>) -> Result<Vec<Item>, ApiError> {
+        self.get(&["items"], token).await
+    }
+
+    pub async fn item(&self, id: &str, token: Option<&str>) -> Result<Item, ApiError> {
+        self.get(&["items", id], token).await
+    }
+
+    /// `GET` the path segments, after the base url, and decode the JSON body
+    pub async fn get<T>(&self, segments: &[&str], token: Option<&str>) -> Result<T, ApiError>
+    where
+        T: DeserializeOwned,
+    {
+        let request = self.request(Method::GET, segments, token)?;
+        self.send(request).await
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        segments: &[&str],
+        token: Option<&str>,
+    ) -> Result<RequestBuilder, ApiError> {
+        let url = self.url(segments)?;
+        let request = self.http.request(method, url);
+        Ok(match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        })
+    }
+
+    /// The base url with the segments appended, each one escaped so that
+    /// ids can't reach other paths or change the query
+    pub fn url(&self, segments: &[&str]) -> Result<Url, ApiError> {
+        // `.` and `..` would be dropped rather than escaped
+        if let Some(segment) = segments
+            .iter()
+            .find(|segment| matches!(**segment, "" | "." | ".."))
+        {
+            return Err(ApiError::InvalidArgument(format!(
+                "`{}` is not a valid path segment",
+                segment
+            )));
+        }
+        let mut url = self.config.base_url.clone();
+        let cannot_be_base = format!("{} cannot be a base url", url);
+        url.path_segments_mut()
+            .map_err(|_| ApiError::Config(cannot_be_base))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// Send the request, retrying transient failures according to the policy
+    async fn send<T>(&self, request: RequestBuilder) -> Result<T, ApiError>
+    where
+        T: DeserializeOwned,
+    {
+        let policy = &self.config.retry;
+        let mut attempt = 1;
+        loop {
+            let this_attempt = request
+                .try_clone()
+                .ok_or_else(|| ApiError::Config(String::from("Request cannot be retried")))?;
+            let (error, retry_after) = match send_once(this_attempt).await {
+                Ok(value) => return Ok(value),
+                Err(failure) => failure,
+            };
+            if !policy.should_retry(&error, attempt) {
+                return Err(error);
+            }
+
+            attempt += 1;
+            let notice = RetryNotice {
+                attempt,
+                max_attempts: policy.max_attempts,
+                delay: policy.delay(attempt - 1, retry_after),
+                error,
+            };
+            warn!("{}", notice);
+            let delay = notice.delay;
+            if let Some(on_retry) = &self.on_retry {
+                on_retry(notice);
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Single attempt, failures come with the server `Retry-After` if any
+async fn send_once<T>(request: RequestBuilder) -> Result<T, (ApiError, Option<Duration>)>
+where
+    T: DeserializeOwned,
+{
+    let response = request.send().await.map_err(|err| (err.into(), None))?;
+    let status = response.status();
+    let retry_after = retry::retry_after(response.headers());
+    let body = response
+        .bytes()
+        .await
+        .map_err(|err| (err.into(), retry_after))?;
+
+    if status.is_client_error() || status.is_server_error() {
+        let message = String::from_utf8_lossy(&body).trim().to_string();
+        let message = if message.is_empty() {
+            status.canonical_reason().unwrap_or_default().to_string()
+        } else {
+            message
+        };
+        let status = status.as_u16();
+        let error = if status >= 500 {
+            ApiError::Server { status, message }
+        } else {
+            ApiError::Client { status, message }
+        };
+        return Err((error, retry_after));
+    }
+
+    serde_json::from_slice(&body).map_err(|err| (ApiError::Decode(err.to_string()), None))
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// A resource exposed by the backend under `/items`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Item {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
@@ -0,0 +1,107 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use super::error::ApiError;
+
+/// How failed requests are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, the first one included
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each following one
+    pub base_delay: Duration,
+    /// Upper bound of the delay, `Retry-After` included
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Whether the failure is worth another attempt
+    pub fn should_retry(&self, error: &ApiError, attempt: u32) -> bool {
+        attempt < self.max_attempts
+            && match error {
+                ApiError::Timeout | ApiError::Network(_) => true,
+                ApiError::Client { status, .. } => *status == 429,
+                ApiError::Server { status, .. } => matches!(status, 500 | 502 | 503 | 504),
+                _ => false,
+            }
+    }
+
+    /// Exponential backoff with jitter, unless the server told us how long to wait
+    ///
+    /// A server asking for more than `max_delay` gets `max_delay`, a job
+    /// shouldn't sit there retrying for hours.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(self.max_delay);
+        }
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        // Keep at least half of the delay, randomize the rest
+        let half = exp / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+}
+
+/// Read a `Retry-After` header, either delay seconds or an HTTP date
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Sent before waiting for the next attempt
+#[derive(Debug, Clone)]
+pub struct RetryNotice {
+    /// Attempt about to be made
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay: Duration,
+    pub error: ApiError,
+}
+
+impl Display for RetryNotice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Retrying (attempt {}/{}) in {:.1}s: {}",
+            self.attempt,
+            self.max_attempts,
+            self.delay.as_secs_f64(),
+            self.error
+        )
+    }
+}
+
+pub type RetryNotifier = Arc<dyn Fn(RetryNotice) + Send + Sync>;
@@ -4,99 +4,400 @@ use std::{
     slice::Iter,
 };
 
-use crate::inputs::key::Key;
+use serde::{de, Deserialize, Deserializer};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::inputs::key::{Key, KeyCode, KeySequence};
+
+use super::keymap::Keymap;
+
+/// Actions are named in kebab-case in keymap files, e.g. `fetch-items`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Action {
     Quit,
+    Suspend,
     Sleep,
     IncrementDelay,
     DecrementDelay,
+    Login,
+    FetchItems,
+    CancelJob,
+    /// Run the last repeatable action again
+    Repeat,
+    /// Search and run any action by name
+    CommandPalette,
+    /// Type a command, with arguments
+    CommandLine,
+    /// Go back to the previous screen
+    Back,
+    ShowItems,
+    /// Edit the delay and API URL in a form
+    Settings,
 }
 
 impl Action {
     pub fn iterator() -> Iter<'static, Action> {
-        static ACTIONS: [Action; 4] = [
+        static ACTIONS: [Action; 14] = [
             Action::Quit,
+            Action::Suspend,
             Action::Sleep,
             Action::IncrementDelay,
             Action::DecrementDelay,
+            Action::Login,
+            Action::FetchItems,
+            Action::CancelJob,
+            Action::Repeat,
+            Action::CommandPalette,
+            Action::CommandLine,
+            Action::Back,
+            Action::ShowItems,
+            Action::Settings,
         ];
         ACTIONS.iter()
     }
 
-    pub fn keys(&self) -> &[Key] {
+    /// Name in keymap files and on the command line
+    pub fn name(&self) -> &'static str {
         match self {
-            Action::Quit => &[Key::Char('q'), Key::Ctrl('c')],
-            Action::Sleep => &[Key::Char('s')],
-            Action::IncrementDelay => &[Key::Char('i')],
-            Action::DecrementDelay => &[Key::Char('d')],
+            Action::Quit => "quit",
+            Action::Suspend => "suspend",
+            Action::Sleep => "sleep",
+            Action::IncrementDelay => "increment-delay",
+            Action::DecrementDelay => "decrement-delay",
+            Action::Login => "login",
+            Action::FetchItems => "fetch-items",
+            Action::CancelJob => "cancel-job",
+            Action::Repeat => "repeat",
+            Action::CommandPalette => "command-palette",
+            Action::CommandLine => "command-line",
+            Action::Back => "back",
+            Action::ShowItems => "show-items",
+            Action::Settings => "settings",
         }
     }
+
+    /// Whether a count prefix runs the action that many times, e.g. `3.`
+    ///
+    /// The palette hands its count over to the action picked in it.
+    pub fn accepts_count(&self) -> bool {
+        matches!(
+            self,
+            Action::IncrementDelay
+                | Action::DecrementDelay
+                | Action::Repeat
+                | Action::CommandPalette
+        )
+    }
+
+    /// Whether `Repeat` can run the action again
+    pub fn is_repeatable(&self) -> bool {
+        !matches!(
+            self,
+            Action::Quit
+                | Action::Suspend
+                | Action::Repeat
+                | Action::CommandPalette
+                | Action::CommandLine
+                | Action::Back
+                | Action::ShowItems
+                | Action::Settings
+        )
+    }
+
+    /// Keys bound to the action in the current keymap
+    pub fn keys(&self) -> &'static [KeySequence] {
+        Keymap::current().keys(*self)
+    }
+
+    pub fn default_keys(&self) -> Vec<KeySequence> {
+        let keys = match self {
+            Action::Quit => vec![Key::char('q'), Key::ctrl('c')],
+            Action::Suspend => vec![Key::ctrl('z')],
+            Action::Sleep => vec![Key::char('s')],
+            // Quicker than the settings form with a count, e.g. `3+`
+            Action::IncrementDelay => vec![Key::char('+')],
+            Action::DecrementDelay => vec![Key::char('-')],
+            Action::Login => vec![Key::char('l')],
+            Action::FetchItems => vec![Key::char('f')],
+            Action::CancelJob => vec![Key::char('c')],
+            Action::Repeat => vec![Key::char('.')],
+            Action::CommandPalette => vec![Key::ctrl('p')],
+            Action::CommandLine => vec![Key::char(':')],
+            Action::Back => vec![Key::plain(KeyCode::Esc)],
+            // Go to items
+            Action::ShowItems => {
+                return vec![KeySequence::from(Key::char('g')).then(Key::char('i'))]
+            }
+            Action::Settings => return vec![KeySequence::from(Key::char('g')).then(Key::char('s'))],
+        };
+        keys.into_iter().map(KeySequence::from).collect()
+    }
+}
+
+impl<'de> Deserialize<'de> for Action {
+    /// By `Action::name`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Action::iterator()
+            .find(|action| action.name() == name)
+            .copied()
+            .ok_or_else(|| de::Error::custom(format!("unknown action `{}`", name)))
+    }
 }
 
 impl Display for Action {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Action::Quit => write!(f, "Quit"),
+            Action::Suspend => write!(f, "Suspend"),
             Action::Sleep => write!(f, "Sleep"),
             Action::IncrementDelay => write!(f, "Increment Delay"),
             Action::DecrementDelay => write!(f, "Decrement Delay"),
+            Action::Login => write!(f, "Login"),
+            Action::FetchItems => write!(f, "Fetch Items"),
+            Action::CancelJob => write!(f, "Cancel Job"),
+            Action::Repeat => write!(f, "Repeat"),
+            Action::CommandPalette => write!(f, "Command Palette"),
+            Action::CommandLine => write!(f, "Command Line"),
+            Action::Back => write!(f, "Back"),
+            Action::ShowItems => write!(f, "Show Items"),
+            Action::Settings => write!(f, "Settings"),
         }
     }
 }
 
+/// What the keys pressed so far lead to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMatch {
+    /// A whole sequence, its action can run
+    Action(Action),
+    /// The start of one or more sequences, wait for the next key
+    Prefix,
+    /// Nothing bound
+    None,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Actions(Vec<Action>);
 
 impl Actions {
-    pub fn find(&self, key: Key) -> Option<&Action> {
-        Action::iterator()
-            .filter(|action| self.0.contains(action))
-            .find(|action| action.keys().contains(&key))
+    /// Like `try_from`, and no keys may start with one the screen takes
+    /// for itself
+    pub fn for_screen(
+        actions: Vec<Action>,
+        keys: &[Key],
+        screen: &str,
+    ) -> Result<Self, KeyConflictError> {
+        let bindings = || {
+            actions
+                .iter()
+                .flat_map(|action| action.keys().iter().map(|sequence| (sequence, *action)))
+        };
+        let mut conflicts = conflicts(bindings());
+        conflicts.extend(taken(bindings(), keys, screen));
+        if !conflicts.is_empty() {
+            return Err(KeyConflictError { conflicts });
+        }
+
+        // Ok, we can create contextual actions
+        Ok(Self(actions))
+    }
+
+    /// Match the keys pressed so far against the sequences of these actions
+    pub fn find(&self, keys: &[Key]) -> KeyMatch {
+        let mut bindings = self
+            .bindings()
+            .filter(|(sequence, _)| sequence.starts_with(keys));
+        match bindings.next() {
+            // Sequences don't conflict, so a whole one can't be the prefix of another
+            Some((sequence, action)) if sequence.keys() == keys => KeyMatch::Action(action),
+            Some(_) => KeyMatch::Prefix,
+            None => KeyMatch::None,
+        }
+    }
+
+    /// Sequences that can complete the keys pressed so far, with their action
+    pub fn continuations(&self, keys: &[Key]) -> Vec<(&'static KeySequence, Action)> {
+        self.bindings()
+            .filter(|(sequence, _)| {
+                sequence.keys().len() > keys.len() && sequence.starts_with(keys)
+            })
+            .collect()
     }
 
     pub fn actions(&self) -> &[Action] {
         self.0.as_slice()
     }
+
+    fn bindings(&self) -> impl Iterator<Item = (&'static KeySequence, Action)> + '_ {
+        Action::iterator()
+            .filter(|action| self.0.contains(action))
+            .flat_map(|action| action.keys().iter().map(|sequence| (sequence, *action)))
+    }
 }
 
-impl From<Vec<Action>> for Actions {
-    /// Build contextual action
-    ///
-    /// # Panics
-    ///
-    /// If two actions have same key
-    fn from(actions: Vec<Action>) -> Self {
-        // Check key unicity
-        let mut map: HashMap<Key, Vec<Action>> = HashMap::new();
-        for action in actions.iter() {
-            for key in action.keys().iter() {
-                match map.get_mut(key) {
-                    Some(vec) => vec.push(*action),
-                    None => {
-                        map.insert(*key, vec![*action]);
-                    }
-                }
-            }
+/// Bindings that can't all be reached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyConflict {
+    /// The same keys are bound to several actions
+    Shared {
+        keys: KeySequence,
+        actions: Vec<Action>,
+    },
+    /// The keys of an action start with the whole keys of another
+    Shadowed {
+        keys: KeySequence,
+        action: Action,
+        prefix: KeySequence,
+        by: Action,
+    },
+    /// The keys of an action start with a key the screen handles itself
+    Taken {
+        keys: KeySequence,
+        action: Action,
+        screen: String,
+    },
+}
+
+impl KeyConflict {
+    /// Actions that can't be reached through these keys
+    pub fn actions(&self) -> Vec<Action> {
+        match self {
+            KeyConflict::Shared { actions, .. } => actions.clone(),
+            KeyConflict::Shadowed { action, by, .. } => vec![*action, *by],
+            KeyConflict::Taken { action, .. } => vec![*action],
         }
-        let errors = map
-            .iter()
-            .filter(|(_, actions)| actions.len() > 1) // at least two actions share same shortcut
-            .map(|(key, actions)| {
+    }
+}
+
+impl Display for KeyConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyConflict::Shared { keys, actions } => {
                 let actions = actions
                     .iter()
                     .map(Action::to_string)
                     .collect::<Vec<_>>()
                     .join(", ");
-                format!("Conflict key {} with actions {}", key, actions)
-            })
-            .collect::<Vec<_>>();
-        if !errors.is_empty() {
-            panic!("{}", errors.join("; "))
+                write!(f, "{} is bound to several actions: {}", keys, actions)
+            }
+            KeyConflict::Shadowed {
+                keys,
+                action,
+                prefix,
+                by,
+            } => write!(
+                f,
+                "{} ({}) is shadowed by {} ({})",
+                keys, action, prefix, by
+            ),
+            KeyConflict::Taken {
+                keys,
+                action,
+                screen,
+            } => write!(f, "{} ({}) is taken by the {} screen", keys, action, screen),
         }
+    }
+}
 
-        // Ok, we can create contextual actions
-        Self(actions)
+/// Every conflict between the given bindings, sorted
+pub fn conflicts<'a>(
+    bindings: impl IntoIterator<Item = (&'a KeySequence, Action)>,
+) -> Vec<KeyConflict> {
+    let bindings = bindings.into_iter().collect::<Vec<_>>();
+    let mut same: HashMap<&KeySequence, Vec<Action>> = HashMap::new();
+    let mut conflicts = vec![];
+    for (sequence, action) in bindings.iter() {
+        let actions = same.entry(*sequence).or_default();
+        if !actions.contains(action) {
+            actions.push(*action);
+        }
+        for (prefix, other) in bindings.iter() {
+            if other != action
+                && prefix.keys().len() < sequence.keys().len()
+                && sequence.starts_with(prefix.keys())
+            {
+                conflicts.push(KeyConflict::Shadowed {
+                    keys: (*sequence).clone(),
+                    action: *action,
+                    prefix: (*prefix).clone(),
+                    by: *other,
+                });
+            }
+        }
+    }
+    conflicts.extend(
+        same.into_iter()
+            .filter(|(_, actions)| actions.len() > 1) // at least two actions share same shortcut
+            .map(|(keys, actions)| KeyConflict::Shared {
+                keys: keys.clone(),
+                actions,
+            }),
+    );
+    conflicts.sort_by_cached_key(KeyConflict::to_string);
+    conflicts
+}
+
+/// Bindings that start with a key the screen handles before the keymap, sorted
+pub fn taken<'a>(
+    bindings: impl IntoIterator<Item = (&'a KeySequence, Action)>,
+    keys: &[Key],
+    screen: &str,
+) -> Vec<KeyConflict> {
+    let mut conflicts = bindings
+        .into_iter()
+        .filter(|(sequence, _)| {
+            sequence
+                .keys()
+                .first()
+                .is_some_and(|key| keys.contains(key))
+        })
+        .map(|(sequence, action)| KeyConflict::Taken {
+            keys: sequence.clone(),
+            action,
+            screen: screen.to_string(),
+        })
+        .collect::<Vec<_>>();
+    conflicts.sort_by_cached_key(KeyConflict::to_string);
+    conflicts
+}
+
+/// Contextual actions whose keys conflict
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyConflictError {
+    pub conflicts: Vec<KeyConflict>,
+}
+
+impl KeyConflictError {
+    /// Actions involved in any conflict
+    pub fn actions(&self) -> Vec<Action> {
+        let mut actions = vec![];
+        for action in self.conflicts.iter().flat_map(KeyConflict::actions) {
+            if !actions.contains(&action) {
+                actions.push(action);
+            }
+        }
+        actions
+    }
+}
+
+impl Display for KeyConflictError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Conflicting key bindings:")?;
+        for conflict in self.conflicts.iter() {
+            write!(f, "\n  - {}", conflict)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for KeyConflictError {}
+
+impl TryFrom<Vec<Action>> for Actions {
+    type Error = KeyConflictError;
+
+    /// Build contextual action, unless two actions have the same keys
+    /// or the keys of one start the keys of another
+    fn try_from(actions: Vec<Action>) -> Result<Self, Self::Error> {
+        Self::for_screen(actions, &[], "")
     }
 }
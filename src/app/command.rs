@@ -0,0 +1,389 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+    time::Duration,
+};
+
+use log::LevelFilter;
+
+use super::{
+    actions::Action,
+    input::TextInput,
+    state::{MAX_DELAY, MIN_DELAY},
+};
+
+/// Commands taking arguments, on top of the actions
+const COMMANDS: [(&str, &str); 4] = [
+    ("sleep", "[seconds]"),
+    ("delay", "<seconds>"),
+    ("log-level", "<off|error|warn|info|debug|trace>"),
+    ("fetch-item", "<id>"),
+];
+
+const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+/// A line typed after `:`, e.g. `sleep 3`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Any action by its keymap name, with an optional count, e.g. `increment-delay 3`
+    Action(Action, Option<u32>),
+    /// Sleep for that long instead of the delay
+    Sleep(Duration),
+    /// Set the sleep delay, in seconds
+    Delay(u64),
+    LogLevel(LevelFilter),
+    FetchItem(String),
+    /// A command missing its argument, to ask for
+    Ask(ArgCommand),
+}
+
+/// Commands that can't run without their argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgCommand {
+    Delay,
+    LogLevel,
+    FetchItem,
+}
+
+impl ArgCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArgCommand::Delay => "delay",
+            ArgCommand::LogLevel => "log-level",
+            ArgCommand::FetchItem => "fetch-item",
+        }
+    }
+
+    /// Action that has to be available to run the command, if any
+    pub fn action(&self) -> Option<Action> {
+        match self {
+            ArgCommand::Delay => Some(Action::IncrementDelay),
+            ArgCommand::LogLevel => None,
+            ArgCommand::FetchItem => Some(Action::FetchItems),
+        }
+    }
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Action(action, _) => action.name(),
+            Command::Sleep(_) => "sleep",
+            Command::Delay(_) => "delay",
+            Command::LogLevel(_) => "log-level",
+            Command::FetchItem(_) => "fetch-item",
+            Command::Ask(command) => command.name(),
+        }
+    }
+
+    /// Action that has to be available to run the command, if any
+    pub fn action(&self) -> Option<Action> {
+        match self {
+            Command::Action(action, _) => Some(*action),
+            Command::Sleep(_) => Some(Action::Sleep),
+            Command::Delay(_) => ArgCommand::Delay.action(),
+            Command::LogLevel(_) => ArgCommand::LogLevel.action(),
+            Command::FetchItem(_) => ArgCommand::FetchItem.action(),
+            Command::Ask(command) => command.action(),
+        }
+    }
+}
+
+/// Why a command line can't run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    Unknown(String),
+    /// Wrong number of arguments
+    Usage(String),
+    /// An argument that doesn't parse
+    Invalid {
+        value: String,
+        expected: String,
+    },
+    /// The command can't run right now
+    Unavailable(String),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Unknown(name) => write!(f, "Not a command: {}", name),
+            CommandError::Usage(name) => write!(f, "Usage: :{} {}", name, usage(name)),
+            CommandError::Invalid { value, expected } => {
+                write!(f, "Invalid argument `{}`, expected {}", value, expected)
+            }
+            CommandError::Unavailable(name) => write!(f, "Not available here: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl FromStr for Command {
+    type Err = CommandError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim().trim_start_matches(':');
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args = words.collect::<Vec<_>>();
+        match (name, args.as_slice()) {
+            ("sleep", []) => Ok(Command::Action(Action::Sleep, None)),
+            ("sleep", [secs]) => Ok(Command::Sleep(Duration::from_secs(parse_secs(secs)?))),
+            ("delay", [secs]) => Ok(Command::Delay(parse_secs(secs)?)),
+            ("log-level", [level]) => level
+                .parse()
+                .map(Command::LogLevel)
+                .map_err(|_| invalid(level, LOG_LEVELS.join(", "))),
+            ("fetch-item", [id]) => Ok(Command::FetchItem(id.to_string())),
+            ("delay", []) => Ok(Command::Ask(ArgCommand::Delay)),
+            ("log-level", []) => Ok(Command::Ask(ArgCommand::LogLevel)),
+            ("fetch-item", []) => Ok(Command::Ask(ArgCommand::FetchItem)),
+            _ if COMMANDS.iter().any(|(command, _)| *command == name) => {
+                Err(CommandError::Usage(name.to_string()))
+            }
+            _ => {
+                let action = Action::iterator()
+                    .find(|action| action.name() == name)
+                    .ok_or_else(|| CommandError::Unknown(name.to_string()))?;
+                match args.as_slice() {
+                    [] => Ok(Command::Action(*action, None)),
+                    [count] if action.accepts_count() => count
+                        .parse()
+                        .ok()
+                        .filter(|count| *count > 0)
+                        .map(|count| Command::Action(*action, Some(count)))
+                        .ok_or_else(|| invalid(count, "a count".to_string())),
+                    _ => Err(CommandError::Usage(name.to_string())),
+                }
+            }
+        }
+    }
+}
+
+fn parse_secs(secs: &str) -> Result<u64, CommandError> {
+    secs.parse()
+        .ok()
+        .filter(|secs| (MIN_DELAY..=MAX_DELAY).contains(secs))
+        .ok_or_else(|| {
+            invalid(
+                secs,
+                format!("seconds between {} and {}", MIN_DELAY, MAX_DELAY),
+            )
+        })
+}
+
+fn invalid(value: &str, expected: String) -> CommandError {
+    CommandError::Invalid {
+        value: value.to_string(),
+        expected,
+    }
+}
+
+/// Arguments of the command, `[count]` for actions taking one
+pub fn usage(name: &str) -> &'static str {
+    COMMANDS
+        .iter()
+        .find(|(command, _)| *command == name)
+        .map(|(_, usage)| *usage)
+        .or_else(|| {
+            Action::iterator()
+                .find(|action| action.name() == name)
+                .map(|action| {
+                    if action.accepts_count() {
+                        "[count]"
+                    } else {
+                        ""
+                    }
+                })
+        })
+        .unwrap_or_default()
+}
+
+/// Every command name, sorted
+pub fn names() -> Vec<&'static str> {
+    let mut names = COMMANDS
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(Action::iterator().map(Action::name))
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Whole lines the line can be completed to, the command name
+/// or its argument, `item_ids` being the values of `fetch-item`
+pub fn completions(line: &str, item_ids: &[String]) -> Vec<String> {
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    match (words.as_slice(), line.ends_with(char::is_whitespace)) {
+        ([], _) => names().into_iter().map(String::from).collect(),
+        ([name], false) => names()
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(name))
+            .map(String::from)
+            .collect(),
+        ([name], true) => arg_completions(name, "", item_ids),
+        ([name, arg], false) => arg_completions(name, arg, item_ids),
+        _ => vec![],
+    }
+}
+
+fn arg_completions(name: &str, prefix: &str, item_ids: &[String]) -> Vec<String> {
+    let values = match name {
+        "fetch-item" => item_ids.to_vec(),
+        _ => arg_values(name),
+    };
+    values
+        .into_iter()
+        .filter(|value| value.starts_with(prefix))
+        .map(|value| format!("{} {}", name, value))
+        .collect()
+}
+
+/// Every value the argument of the command can take, empty if it's free
+pub fn arg_values(name: &str) -> Vec<String> {
+    match name {
+        "sleep" | "delay" => (MIN_DELAY..=MAX_DELAY).map(|s| s.to_string()).collect(),
+        "log-level" => LOG_LEVELS.iter().map(|s| s.to_string()).collect(),
+        _ => vec![],
+    }
+}
+
+/// Candidates being cycled through with Tab
+#[derive(Debug, Clone)]
+struct Completion {
+    candidates: Vec<String>,
+    index: usize,
+}
+
+/// The `:` command line, its history kept between uses
+#[derive(Debug, Clone, Default)]
+pub struct CommandLine {
+    open: bool,
+    input: TextInput,
+    error: Option<String>,
+    completion: Option<Completion>,
+}
+
+impl CommandLine {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.input.clear();
+        self.reset();
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.input.clear();
+        self.reset();
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn input(&self) -> &str {
+        self.input.value()
+    }
+
+    pub fn field(&self) -> &TextInput {
+        &self.input
+    }
+
+    /// Error of the last command, until the line is edited
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    /// Candidates while completing, and the current one
+    pub fn completions(&self) -> Option<(&[String], usize)> {
+        self.completion
+            .as_ref()
+            .map(|completion| (completion.candidates.as_slice(), completion.index))
+    }
+
+    pub fn history(&self) -> &[String] {
+        self.input.history()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.edit(|input| input.insert(c));
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.edit(|input| input.paste(s));
+    }
+
+    pub fn pop(&mut self) {
+        self.edit(TextInput::backspace);
+    }
+
+    /// Change the line, which drops its error and completions
+    pub fn edit<T>(&mut self, edit: impl FnOnce(&mut TextInput) -> T) -> T {
+        let before = self.input.value().to_string();
+        let result = edit(&mut self.input);
+        if self.input.value() != before {
+            self.reset();
+        }
+        result
+    }
+
+    fn reset(&mut self) {
+        self.error = None;
+        self.completion = None;
+    }
+
+    /// Complete the line, or go to the next (previous if `backward`) candidate
+    pub fn complete(&mut self, backward: bool, item_ids: &[String]) {
+        let completion = match self.completion.take() {
+            Some(mut completion) => {
+                let count = completion.candidates.len();
+                completion.index = if backward {
+                    (completion.index + count - 1) % count
+                } else {
+                    (completion.index + 1) % count
+                };
+                completion
+            }
+            None => {
+                let candidates = completions(self.input.value(), item_ids);
+                if candidates.is_empty() {
+                    return;
+                }
+                let index = if backward { candidates.len() - 1 } else { 0 };
+                Completion { candidates, index }
+            }
+        };
+        self.input
+            .set_value(completion.candidates[completion.index].clone());
+        // A single candidate is done, Tab can complete the next word
+        if completion.candidates.len() > 1 {
+            self.completion = Some(completion);
+        }
+        self.error = None;
+    }
+
+    /// Replace the line with the previous one in history
+    pub fn history_previous(&mut self) {
+        self.input.history_previous();
+        self.reset();
+    }
+
+    /// Replace the line with the next one in history, back to what was typed
+    pub fn history_next(&mut self) {
+        self.input.history_next();
+        self.reset();
+    }
+
+    /// Parse the line, keeping it in history
+    pub fn submit(&mut self) -> Result<Command, CommandError> {
+        self.input.commit();
+        self.completion = None;
+        self.input.value().parse()
+    }
+}
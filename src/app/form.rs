@@ -0,0 +1,397 @@
+use url::Url;
+
+use crate::{
+    inputs::key::{Key, KeyCode, Modifiers},
+    io::IoEvent,
+};
+
+use super::input::TextInput;
+
+/// Checks a field value, the error is shown under the field
+pub type Validator = fn(&Value) -> Result<(), String>;
+
+/// A list of fields, submitted as one `IoEvent`
+#[derive(Debug, Clone)]
+pub struct Form {
+    fields: Vec<Field>,
+    focused: usize,
+    /// Turns the valid values into the job that applies them
+    submit: fn(&FormValues) -> IoEvent,
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    name: &'static str,
+    label: &'static str,
+    kind: FieldKind,
+    validators: Vec<Validator>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum FieldKind {
+    Text(TextInput),
+    /// Digits only, other chars are swallowed rather than left to the keymap
+    Number {
+        input: TextInput,
+        min: u64,
+        max: u64,
+    },
+    Toggle(bool),
+    /// One of the options, changed with Left and Right
+    Select {
+        options: Vec<String>,
+        selected: usize,
+    },
+}
+
+/// Value of a field, once it parses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Text(String),
+    Number(u64),
+    Toggle(bool),
+    Select(String),
+}
+
+/// Values of a valid form, by field name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormValues(Vec<(&'static str, Value)>);
+
+/// What a key did to the form
+#[derive(Debug, Clone)]
+pub enum FormKey {
+    /// Not for the form, the keymap can have it
+    Ignored,
+    Handled,
+    /// Enter on a valid form
+    Submit(IoEvent),
+}
+
+impl Form {
+    pub fn new(submit: fn(&FormValues) -> IoEvent) -> Self {
+        Self {
+            fields: vec![],
+            focused: 0,
+            submit,
+        }
+    }
+
+    pub fn field(mut self, field: Field) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Index of the focused field
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    /// The text being typed in the focused field, if it takes text
+    pub fn input(&self) -> Option<&TextInput> {
+        self.fields.get(self.focused).and_then(Field::input)
+    }
+
+    /// Edit the focused field, `None` if it doesn't take text
+    ///
+    /// Changing the value clears its error, until it's validated again.
+    pub fn edit<T>(&mut self, edit: impl FnOnce(&mut TextInput) -> T) -> Option<T> {
+        let field = self.fields.get_mut(self.focused)?;
+        let input = match &mut field.kind {
+            FieldKind::Text(input) | FieldKind::Number { input, .. } => input,
+            _ => return None,
+        };
+        let before = input.value().to_string();
+        let result = edit(input);
+        if input.value() != before {
+            field.error = None;
+        }
+        Some(result)
+    }
+
+    /// Keys taken by `on_key`, besides the text typed in a field
+    pub fn keys(&self) -> Vec<Key> {
+        let mut keys = [
+            KeyCode::Tab,
+            KeyCode::Down,
+            KeyCode::Up,
+            KeyCode::Enter,
+            KeyCode::Left,
+            KeyCode::Right,
+        ]
+        .map(Key::plain)
+        .to_vec();
+        keys.extend([Key::new(KeyCode::Tab, Modifiers::SHIFT), Key::char(' ')]);
+        keys
+    }
+
+    /// Move the focus with Tab, Shift+Tab, Up and Down, change the focused
+    /// field and submit with Enter
+    ///
+    /// Leaving a field validates it.
+    pub fn on_key(&mut self, key: Key) -> FormKey {
+        let shift = key.modifiers == Modifiers::SHIFT;
+        let plain = key.modifiers.is_empty();
+        match key.code {
+            KeyCode::Tab if shift => self.focus_previous(),
+            KeyCode::Tab | KeyCode::Down if plain => self.focus_next(),
+            KeyCode::Up if plain => self.focus_previous(),
+            KeyCode::Enter if plain => {
+                return match self.submit() {
+                    Some(values) => FormKey::Submit((self.submit)(&values)),
+                    None => FormKey::Handled,
+                }
+            }
+            _ => {
+                let handled = match self.fields.get_mut(self.focused) {
+                    Some(field) => field.on_key(key),
+                    None => false,
+                };
+                if !handled {
+                    return FormKey::Ignored;
+                }
+            }
+        }
+        FormKey::Handled
+    }
+
+    /// Focus the next field, wrapping around
+    pub fn focus_next(&mut self) {
+        self.validate_focused();
+        if !self.fields.is_empty() {
+            self.focused = (self.focused + 1) % self.fields.len();
+        }
+    }
+
+    /// Focus the previous field, wrapping around
+    pub fn focus_previous(&mut self) {
+        self.validate_focused();
+        if !self.fields.is_empty() {
+            self.focused = (self.focused + self.fields.len() - 1) % self.fields.len();
+        }
+    }
+
+    fn validate_focused(&mut self) {
+        if let Some(field) = self.fields.get_mut(self.focused) {
+            field.validate();
+        }
+    }
+
+    /// Validate every field, the values if they're all valid, otherwise
+    /// the first invalid field gets the focus
+    pub fn submit(&mut self) -> Option<FormValues> {
+        let values = self
+            .fields
+            .iter_mut()
+            .map(|field| Some((field.name, field.validate()?)))
+            .collect::<Vec<_>>();
+        match values.iter().position(Option::is_none) {
+            Some(invalid) => {
+                self.focused = invalid;
+                None
+            }
+            None => Some(FormValues(values.into_iter().flatten().collect())),
+        }
+    }
+}
+
+impl Field {
+    pub fn text(name: &'static str, label: &'static str, value: &str) -> Self {
+        let mut input = TextInput::new();
+        input.set_value(value);
+        Self::new(name, label, FieldKind::Text(input))
+    }
+
+    /// A text field shown masked
+    pub fn secret(name: &'static str, label: &'static str, value: &str) -> Self {
+        let mut input = TextInput::masked();
+        input.set_value(value);
+        Self::new(name, label, FieldKind::Text(input))
+    }
+
+    pub fn number(name: &'static str, label: &'static str, value: u64, min: u64, max: u64) -> Self {
+        let mut input = TextInput::digits();
+        input.set_value(value.to_string());
+        Self::new(name, label, FieldKind::Number { input, min, max })
+    }
+
+    pub fn toggle(name: &'static str, label: &'static str, value: bool) -> Self {
+        Self::new(name, label, FieldKind::Toggle(value))
+    }
+
+    /// The first option is selected unless `value` is one of them
+    pub fn select(
+        name: &'static str,
+        label: &'static str,
+        options: Vec<String>,
+        value: &str,
+    ) -> Self {
+        let selected = options
+            .iter()
+            .position(|option| option == value)
+            .unwrap_or_default();
+        Self::new(name, label, FieldKind::Select { options, selected })
+    }
+
+    fn new(name: &'static str, label: &'static str, kind: FieldKind) -> Self {
+        Self {
+            name,
+            label,
+            kind,
+            validators: vec![],
+            error: None,
+        }
+    }
+
+    /// Also check the value with `validator`, in the order they're added
+    pub fn validate_with(mut self, validator: Validator) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn kind(&self) -> &FieldKind {
+        &self.kind
+    }
+
+    /// Why the value is invalid, since it was last validated
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn input(&self) -> Option<&TextInput> {
+        match &self.kind {
+            FieldKind::Text(input) | FieldKind::Number { input, .. } => Some(input),
+            _ => None,
+        }
+    }
+
+    /// The value, or why it isn't valid
+    pub fn value(&self) -> Result<Value, String> {
+        let value = match &self.kind {
+            FieldKind::Text(input) => Value::Text(input.value().trim().to_string()),
+            FieldKind::Number { input, min, max } => {
+                let number = input
+                    .value()
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|number| (min..=max).contains(&number))
+                    .ok_or_else(|| format!("Must be a number between {} and {}", min, max))?;
+                Value::Number(number)
+            }
+            FieldKind::Toggle(on) => Value::Toggle(*on),
+            FieldKind::Select { options, selected } => {
+                Value::Select(options.get(*selected).cloned().unwrap_or_default())
+            }
+        };
+        self.validators
+            .iter()
+            .try_for_each(|validator| validator(&value))?;
+        Ok(value)
+    }
+
+    /// Keep the error to show it, `None` when the value is invalid
+    fn validate(&mut self) -> Option<Value> {
+        match self.value() {
+            Ok(value) => {
+                self.error = None;
+                Some(value)
+            }
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+
+    fn on_key(&mut self, key: Key) -> bool {
+        let plain = key.modifiers.is_empty();
+        let handled = match &mut self.kind {
+            FieldKind::Text(input) | FieldKind::Number { input, .. } => input.on_key(key),
+            FieldKind::Toggle(on) => match key.code {
+                KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right if plain => {
+                    *on = !*on;
+                    true
+                }
+                _ => false,
+            },
+            FieldKind::Select { options, selected } if !options.is_empty() => match key.code {
+                KeyCode::Left if plain => {
+                    *selected = (*selected + options.len() - 1) % options.len();
+                    true
+                }
+                KeyCode::Right | KeyCode::Char(' ') if plain => {
+                    *selected = (*selected + 1) % options.len();
+                    true
+                }
+                _ => false,
+            },
+            FieldKind::Select { .. } => false,
+        };
+        if handled {
+            self.error = None;
+        }
+        handled
+    }
+}
+
+impl FormValues {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            Value::Text(text) | Value::Select(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self, name: &str) -> Option<u64> {
+        match self.get(name)? {
+            Value::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn toggle(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            Value::Toggle(on) => Some(*on),
+            _ => None,
+        }
+    }
+}
+
+/// Text fields that can't be left empty
+pub fn required(value: &Value) -> Result<(), String> {
+    match value {
+        Value::Text(text) if text.is_empty() => Err(String::from("Required")),
+        _ => Ok(()),
+    }
+}
+
+/// Text fields holding an http(s) URL, or nothing
+pub fn http_url(value: &Value) -> Result<(), String> {
+    match value {
+        Value::Text(text) if !text.is_empty() => match Url::parse(text) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+            Ok(url) => Err(format!("Expected http or https, not {}", url.scheme())),
+            Err(err) => Err(format!("Invalid URL: {}", err)),
+        },
+        _ => Ok(()),
+    }
+}
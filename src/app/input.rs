@@ -0,0 +1,308 @@
+use unicode_width::UnicodeWidthChar;
+
+use crate::inputs::key::{Key, KeyCode, Modifiers};
+
+/// A line of text being edited, with a cursor and its own history
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInput {
+    value: String,
+    /// Byte offset in `value`, always on a char boundary
+    cursor: usize,
+    /// Shown instead of each char, for secrets
+    mask: Option<char>,
+    /// Other chars are dropped, for numbers
+    digits_only: bool,
+    history: Vec<String>,
+    /// Index in the history while going through it, and the line typed before
+    browsing: Option<(usize, String)>,
+}
+
+/// The part of the input that fits, split around the cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputView {
+    pub before: String,
+    /// Char under the cursor, a space at the end of the line
+    pub cursor: String,
+    pub after: String,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows `*` for each char, and keeps no history
+    pub fn masked() -> Self {
+        Self {
+            mask: Some('*'),
+            ..Self::default()
+        }
+    }
+
+    /// Only takes ASCII digits
+    pub fn digits() -> Self {
+        Self {
+            digits_only: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Cursor position, in chars
+    pub fn cursor(&self) -> usize {
+        self.value[..self.cursor].chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    pub fn is_masked(&self) -> bool {
+        self.mask.is_some()
+    }
+
+    /// Replace the text, the cursor goes to its end
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        let mut value = value.into();
+        value.retain(|c| self.accepts(c));
+        self.value = value;
+        self.cursor = self.value.len();
+        self.browsing = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.set_value(String::new());
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Edit or move the cursor, false if the key does neither
+    ///
+    /// Enter, Esc, Tab, Up and Down are left to the owner of the input,
+    /// and so is Backspace at the start of the line.
+    pub fn on_key(&mut self, key: Key) -> bool {
+        let ctrl = key.modifiers == Modifiers::CTRL;
+        let alt = key.modifiers == Modifiers::ALT;
+        let plain = key.modifiers.is_empty();
+        match key.code {
+            KeyCode::Char(c) if plain => self.insert(c),
+            KeyCode::Backspace if plain && self.cursor > 0 => self.backspace(),
+            KeyCode::Delete if plain => self.delete(),
+            KeyCode::Char('d') if ctrl => self.delete(),
+            KeyCode::Char('w') if ctrl => self.delete_word_back(),
+            KeyCode::Char('u') if ctrl => self.delete_to_start(),
+            KeyCode::Char('k') if ctrl => self.delete_to_end(),
+            KeyCode::Left if plain => self.move_left(),
+            KeyCode::Right if plain => self.move_right(),
+            KeyCode::Left if ctrl => self.move_word_left(),
+            KeyCode::Right if ctrl => self.move_word_right(),
+            KeyCode::Char('b') if alt => self.move_word_left(),
+            KeyCode::Char('f') if alt => self.move_word_right(),
+            KeyCode::Home if plain => self.move_to(0),
+            KeyCode::Char('a') if ctrl => self.move_to(0),
+            KeyCode::End if plain => self.move_to(self.value.len()),
+            KeyCode::Char('e') if ctrl => self.move_to(self.value.len()),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Insert at the cursor, control chars (and non-digits if `digits`) are dropped
+    pub fn insert(&mut self, c: char) {
+        if !self.accepts(c) {
+            return;
+        }
+        self.value.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.browsing = None;
+    }
+
+    /// Insert at the cursor, up to the first line break
+    pub fn paste(&mut self, text: &str) {
+        for c in text.lines().next().unwrap_or_default().chars() {
+            self.insert(c);
+        }
+    }
+
+    fn accepts(&self, c: char) -> bool {
+        !c.is_control() && (!self.digits_only || c.is_ascii_digit())
+    }
+
+    pub fn backspace(&mut self) {
+        let start = self.previous_boundary(self.cursor);
+        self.delete_range(start, self.cursor);
+    }
+
+    pub fn delete(&mut self) {
+        let end = self.next_boundary(self.cursor);
+        self.delete_range(self.cursor, end);
+    }
+
+    /// Delete back to the previous whitespace, like a shell
+    pub fn delete_word_back(&mut self) {
+        let before = &self.value[..self.cursor];
+        let start = before
+            .trim_end()
+            .rfind(char::is_whitespace)
+            .map_or(0, |i| i + 1);
+        self.delete_range(start, self.cursor);
+    }
+
+    pub fn delete_to_start(&mut self) {
+        self.delete_range(0, self.cursor);
+    }
+
+    pub fn delete_to_end(&mut self) {
+        self.delete_range(self.cursor, self.value.len());
+    }
+
+    fn delete_range(&mut self, start: usize, end: usize) {
+        if start < end {
+            self.value.replace_range(start..end, "");
+            self.cursor = start;
+            self.browsing = None;
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.previous_boundary(self.cursor);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = self.next_boundary(self.cursor);
+    }
+
+    /// To the start of the word, or of the previous one
+    ///
+    /// Masked inputs don't give their words away, it goes to the start.
+    pub fn move_word_left(&mut self) {
+        if self.is_masked() {
+            return self.move_to(0);
+        }
+        let before = &self.value[..self.cursor];
+        let word_end = before.trim_end_matches(|c: char| !is_word(c)).len();
+        self.cursor = before[..word_end]
+            .rfind(|c: char| !is_word(c))
+            .map_or(0, |i| self.next_boundary(i));
+    }
+
+    /// To the end of the word, or of the next one
+    pub fn move_word_right(&mut self) {
+        if self.is_masked() {
+            return self.move_to(self.value.len());
+        }
+        let after = &self.value[self.cursor..];
+        let word_start = after.len() - after.trim_start_matches(|c: char| !is_word(c)).len();
+        self.cursor += after[word_start..]
+            .find(|c: char| !is_word(c))
+            .map_or(after.len(), |i| word_start + i);
+    }
+
+    fn move_to(&mut self, cursor: usize) {
+        self.cursor = cursor;
+    }
+
+    fn previous_boundary(&self, i: usize) -> usize {
+        self.value[..i].char_indices().last().map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self, i: usize) -> usize {
+        self.value[i..]
+            .chars()
+            .next()
+            .map_or(i, |c| i + c.len_utf8())
+    }
+
+    /// Keep the line in history, unless it's a secret or the last one again
+    pub fn commit(&mut self) {
+        self.browsing = None;
+        let line = self.value.trim();
+        if line.is_empty()
+            || self.is_masked()
+            || self.history.last().map(String::as_str) == Some(line)
+        {
+            return;
+        }
+        self.history.push(line.to_string());
+    }
+
+    /// Replace the line with the previous one in history
+    pub fn history_previous(&mut self) {
+        let index = match &self.browsing {
+            Some((0, _)) => return,
+            Some((index, _)) => index - 1,
+            None if self.history.is_empty() => return,
+            None => {
+                self.browsing = Some((self.history.len(), self.value.clone()));
+                self.history.len() - 1
+            }
+        };
+        self.browse(index);
+    }
+
+    /// Replace the line with the next one in history, back to what was typed
+    pub fn history_next(&mut self) {
+        match self.browsing.take() {
+            Some((index, draft)) if index + 1 >= self.history.len() => self.set_value(draft),
+            Some((index, draft)) => {
+                self.browsing = Some((index, draft));
+                self.browse(index + 1);
+            }
+            None => {}
+        }
+    }
+
+    fn browse(&mut self, index: usize) {
+        let browsing = self.browsing.take();
+        self.set_value(self.history[index].clone());
+        self.browsing = browsing.map(|(_, draft)| (index, draft));
+    }
+
+    /// What fits in `width` columns, scrolled to keep the cursor in view
+    ///
+    /// Wide chars take two columns and are never cut in half.
+    pub fn view(&self, width: usize) -> InputView {
+        let shown = |s: &str| -> Vec<char> {
+            match self.mask {
+                Some(mask) => s.chars().map(|_| mask).collect(),
+                None => s.chars().collect(),
+            }
+        };
+        let before = shown(&self.value[..self.cursor]);
+        let mut after = shown(&self.value[self.cursor..]).into_iter();
+        let cursor = after.next().unwrap_or(' ');
+
+        let mut used = char_width(cursor);
+        let start = before
+            .iter()
+            .rposition(|c| {
+                used += char_width(*c);
+                used > width
+            })
+            .map_or(0, |i| i + 1);
+        let after = after
+            .take_while(|c| {
+                used += char_width(*c);
+                used <= width
+            })
+            .collect();
+        InputView {
+            before: before[start..].iter().collect(),
+            cursor: cursor.to_string(),
+            after,
+        }
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
@@ -0,0 +1,159 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    time::{Duration, Instant},
+};
+
+use tokio::task::AbortHandle;
+
+use crate::io::{IoEvent, JobId};
+
+/// Finished jobs kept around for reference
+pub const KEEP_FINISHED: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Waiting for a free slot
+    Pending,
+    Running,
+    Succeeded,
+    Failed(String),
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, JobStatus::Pending | JobStatus::Running)
+    }
+}
+
+impl Display for JobStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            JobStatus::Pending => write!(f, "pending"),
+            JobStatus::Running => write!(f, "running"),
+            JobStatus::Succeeded => write!(f, "done"),
+            JobStatus::Failed(_) => write!(f, "failed"),
+            JobStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Job {
+    id: JobId,
+    event: IoEvent,
+    status: JobStatus,
+    created_at: Instant,
+    started_at: Option<Instant>,
+    finished_at: Option<Instant>,
+    abort: Option<AbortHandle>,
+}
+
+impl Job {
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    pub fn event(&self) -> &IoEvent {
+        &self.event
+    }
+
+    pub fn status(&self) -> &JobStatus {
+        &self.status
+    }
+
+    /// Time spent waiting, or running once started
+    pub fn elapsed(&self) -> Duration {
+        let since = self.started_at.unwrap_or(self.created_at);
+        match self.finished_at {
+            Some(finished_at) => finished_at.duration_since(since),
+            None => since.elapsed(),
+        }
+    }
+}
+
+/// Every dispatched IO job, by id
+#[derive(Debug, Default)]
+pub struct Jobs(BTreeMap<JobId, Job>);
+
+impl Jobs {
+    pub fn add(&mut self, id: JobId, event: IoEvent) {
+        let job = Job {
+            id,
+            event,
+            status: JobStatus::Pending,
+            created_at: Instant::now(),
+            started_at: None,
+            finished_at: None,
+            abort: None,
+        };
+        self.0.insert(id, job);
+    }
+
+    pub fn get(&self, id: JobId) -> Option<&Job> {
+        self.0.get(&id)
+    }
+
+    /// Allow cancelling the job
+    pub fn set_abort_handle(&mut self, id: JobId, abort: AbortHandle) {
+        if let Some(job) = self.0.get_mut(&id) {
+            job.abort = Some(abort);
+        }
+    }
+
+    pub fn start(&mut self, id: JobId) {
+        if let Some(job) = self.0.get_mut(&id) {
+            job.status = JobStatus::Running;
+            job.started_at = Some(Instant::now());
+        }
+    }
+
+    /// Record the outcome, returns the job if it was still in flight
+    pub fn finish(&mut self, id: JobId, status: JobStatus) -> Option<&Job> {
+        let job = self
+            .0
+            .get_mut(&id)
+            .filter(|job| !job.status.is_finished())?;
+        job.status = status;
+        job.finished_at = Some(Instant::now());
+        job.abort = None;
+        self.prune();
+        self.0.get(&id)
+    }
+
+    /// Abort the job task, the handler reports it cancelled once stopped
+    pub fn cancel(&mut self, id: JobId) -> bool {
+        match self.0.get(&id).and_then(|job| job.abort.as_ref()) {
+            Some(abort) => {
+                abort.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn in_flight(&self) -> impl DoubleEndedIterator<Item = &Job> {
+        self.0.values().filter(|job| !job.status.is_finished())
+    }
+
+    /// No job is pending or running
+    pub fn is_idle(&self) -> bool {
+        self.in_flight().next().is_none()
+    }
+
+    fn prune(&mut self) {
+        let finished = self
+            .0
+            .values()
+            .filter(|job| job.status.is_finished())
+            .map(Job::id)
+            .collect::<Vec<_>>();
+        for id in finished
+            .iter()
+            .take(finished.len().saturating_sub(KEEP_FINISHED))
+        {
+            self.0.remove(id);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use serde::Deserialize;
+
+use crate::inputs::key::KeySequence;
+
+use super::actions::{self, Action};
+
+const APP_DIR: &str = "churro-cli";
+
+static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+
+/// Key bindings of every action
+#[derive(Debug, Clone)]
+pub struct Keymap(HashMap<Action, Vec<KeySequence>>);
+
+/// Keymap file content, e.g. in TOML
+///
+/// ```toml
+/// [keys]
+/// quit = ["q", "<Ctrl+c>"]
+/// fetch-items = "<F5>"
+/// login = "g l"
+/// ```
+#[derive(Debug, Deserialize)]
+struct KeymapFile {
+    #[serde(default)]
+    keys: HashMap<Action, Bindings>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Bindings {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keymap {
+    /// Keys bound to the action, its defaults unless overridden
+    pub fn keys(&self, action: Action) -> &[KeySequence] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Bind the action to these keys instead, conflicts are left to `Actions`
+    pub fn bind(&mut self, action: Action, keys: Vec<KeySequence>) {
+        self.0.insert(action, keys);
+    }
+
+    /// Load the user keymap, falling back to the defaults if there is none
+    ///
+    /// Looked up in `CHURRO_KEYMAP`, then `keymap.toml` and `keymap.json`
+    /// in the user config dir (e.g. `~/.config/churro-cli/`).
+    pub fn load() -> Result<Self, KeymapError> {
+        match keymap_path() {
+            Some(path) => Self::from_file(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, KeymapError> {
+        let error = |problems| KeymapError {
+            path: path.to_path_buf(),
+            problems,
+        };
+        let content = fs::read_to_string(path).map_err(|err| error(vec![err.to_string()]))?;
+        let file: KeymapFile = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).map_err(|err| error(vec![err.to_string()]))?
+        } else {
+            toml::from_str(&content).map_err(|err| error(vec![err.to_string()]))?
+        };
+        Self::from_bindings(file.keys).map_err(error)
+    }
+
+    /// Merge the bindings over the defaults, listing every problem found
+    fn from_bindings(bindings: HashMap<Action, Bindings>) -> Result<Self, Vec<String>> {
+        let mut problems = vec![];
+        let mut keymap = Self::default();
+        for (action, bindings) in bindings {
+            let bindings = match bindings {
+                Bindings::One(key) => vec![key],
+                Bindings::Many(keys) => keys,
+            };
+            let mut keys = vec![];
+            for binding in bindings {
+                match binding.parse::<KeySequence>() {
+                    Ok(key) => keys.push(key),
+                    Err(err) => problems.push(format!("{}: {}", action, err)),
+                }
+            }
+            keymap.bind(action, keys);
+        }
+
+        let bindings = Action::iterator()
+            .flat_map(|action| keymap.keys(*action).iter().map(|keys| (keys, *action)));
+        problems.extend(actions::conflicts(bindings).iter().map(ToString::to_string));
+
+        if problems.is_empty() {
+            Ok(keymap)
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Make this keymap the one used by `Action::keys`, only the first call counts
+    pub fn install(self) {
+        if KEYMAP.set(self).is_err() {
+            log::warn!("Keymap already installed, ignoring");
+        }
+    }
+
+    /// The installed keymap, the defaults if none was
+    pub fn current() -> &'static Keymap {
+        KEYMAP.get_or_init(Keymap::default)
+    }
+}
+
+impl Default for Keymap {
+    /// Bindings from `Action::default_keys`
+    fn default() -> Self {
+        Self(
+            Action::iterator()
+                .map(|action| (*action, action.default_keys()))
+                .collect(),
+        )
+    }
+}
+
+fn keymap_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("CHURRO_KEYMAP") {
+        return Some(PathBuf::from(path));
+    }
+    let dir = dirs::config_dir()?.join(APP_DIR);
+    ["keymap.toml", "keymap.json"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// Everything wrong with a keymap file
+#[derive(Debug, Clone)]
+pub struct KeymapError {
+    pub path: PathBuf,
+    pub problems: Vec<String>,
+}
+
+impl Display for KeymapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid keymap {}:", self.path.display())?;
+        for problem in self.problems.iter() {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for KeymapError {}
@@ -1,78 +1,838 @@
-use crate::{inputs::key::Key, io::IoEvent};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    api::{error::ApiError, models::Item, retry::RetryNotice},
+    inputs::key::{Key, KeyCode, Modifiers},
+    io::{IoEvent, IoJob, JobId, Settings},
+    logging,
+};
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use tokio::{sync::oneshot, task::AbortHandle};
+use tui::{buffer::Buffer, layout::Rect};
+use url::Url;
 
 use self::{
-    actions::{Action, Actions},
-    state::AppState,
+    actions::{Action, Actions, KeyMatch},
+    command::{self as commands, ArgCommand, Command, CommandError, CommandLine},
+    input::TextInput,
+    jobs::{JobStatus, Jobs},
+    modal::{Modal, ModalAnswer, ModalReply},
+    palette::Palette,
+    screens::{Dashboard, Items, Login, Screen, ScreenContext, ScreenId, ScreenKey, SettingsForm},
+    state::{AppState, AuthStatus},
+    table::TableView,
 };
 
 pub mod actions;
+pub mod command;
+pub mod form;
+pub mod input;
+pub mod jobs;
+pub mod keymap;
+pub mod modal;
+pub mod palette;
+pub mod screens;
 pub mod state;
+pub mod table;
 pub mod ui;
 
-use log::{debug, error, warn};
+use log::{debug, error, info, warn};
+
+/// How long to wait for the next key of a sequence
+pub const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+/// Counts are capped, `99999i` shouldn't freeze the app
+const MAX_COUNT: u32 = 999;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum AppReturn {
     Exit,
     Continue,
+    /// Give the terminal back to the shell until resumed
+    Suspend,
+}
+
+/// Where the keys go first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    /// Keys run the actions of the keymap
+    Normal,
+    /// Keys edit the focused text field, the ones it leaves go to its owner
+    Editing,
+}
+
+/// Results sent back to the UI loop by the IO handler
+#[derive(Debug)]
+pub enum AppEvent {
+    Initialized,
+    LoggedIn,
+    NeedsLogin(String),
+    LoginFailed(String),
+    Slept,
+    ItemsLoaded(Vec<Item>),
+    ItemLoaded(Item),
+    ApiFailed(ApiError),
+    /// A request of the job failed, it's tried again
+    Retrying(JobId, RetryNotice),
+    JobSpawned(JobId, AbortHandle),
+    JobStarted(JobId),
+    JobFinished(JobId, JobStatus),
+    /// Ask the user, the answer goes back through the sender
+    Ask(Modal, oneshot::Sender<ModalAnswer>),
+    /// Base URL of the API at startup, `None` when it's disabled
+    ApiConfigured(Option<Url>),
+    SettingsUpdated(Settings),
 }
 
 pub struct App {
-    io_tx: tokio::sync::mpsc::Sender<IoEvent>,
+    io_tx: tokio::sync::mpsc::Sender<IoJob>,
     actions: Actions,
+    /// Keys of a sequence typed so far, and when the last one (or count digit) was
+    pending: Vec<Key>,
+    pending_at: Option<Instant>,
+    /// Count typed before the keys, e.g. the 5 of `5i`
+    count: Option<u32>,
+    /// Last repeatable action and its count, for `Action::Repeat`
+    last_action: Option<(Action, Option<u32>)>,
+    /// Open command palette, it gets the keys
+    palette: Option<Palette>,
+    /// Gets the keys when open
+    command_line: CommandLine,
     state: AppState,
-    is_loading: bool,
+    auth: AuthStatus,
+    /// Latest retry, until its job is done
+    retry: Option<(JobId, RetryNotice)>,
+    next_job: u64,
+    jobs: Jobs,
+    size: Rect,
+    focused: bool,
+    api_url: Option<Url>,
+    /// Configuration problems to show before anything else
+    diagnostics: Vec<String>,
+    /// Navigation stack, the last screen is shown, never empty
+    screens: Vec<Box<dyn Screen>>,
+    /// Dialogs waiting for an answer, the first one is shown
+    modals: VecDeque<(Modal, ModalReply)>,
+    /// Help pane, scrolled with the mouse wheel
+    help: TableView,
 }
 
 impl App {
-    pub fn new(io_tx: tokio::sync::mpsc::Sender<IoEvent>) -> Self {
-        let actions = vec![Action::Quit].into();
-        let is_loading = false;
+    pub fn new(io_tx: tokio::sync::mpsc::Sender<IoJob>) -> Self {
         let state = AppState::default();
 
-        Self {
+        let mut app = Self {
             io_tx,
-            actions,
+            actions: Actions::default(),
+            pending: vec![],
+            pending_at: None,
+            count: None,
+            last_action: None,
+            palette: None,
+            command_line: CommandLine::default(),
             state,
-            is_loading,
-        }
+            auth: AuthStatus::default(),
+            retry: None,
+            next_job: 0,
+            jobs: Jobs::default(),
+            size: Rect::default(),
+            focused: true,
+            api_url: None,
+            diagnostics: vec![],
+            screens: vec![Box::new(Dashboard)],
+            modals: VecDeque::new(),
+            help: ui::help_table(),
+        };
+        app.refresh_actions();
+        app
     }
 
-    pub async fn dispatch(&mut self, action: IoEvent) {
-        self.is_loading = true;
-        if let Err(e) = self.io_tx.send(action).await {
-            self.is_loading = false;
+    /// Send the event to the IO handler, the returned id is reported back once done
+    pub async fn dispatch(&mut self, event: IoEvent) -> JobId {
+        self.next_job += 1;
+        let id = JobId(self.next_job);
+        self.jobs.add(id, event.clone());
+        if let Err(e) = self.io_tx.send(IoJob { id, event }).await {
+            self.jobs.finish(id, JobStatus::Failed(e.to_string()));
             error!("Error sending IoEvent: {}", e);
         }
+        id
     }
 
     pub async fn do_action(&mut self, key: Key) -> AppReturn {
-        if let Some(action) = self.actions.find(key) {
-            debug!("Doing action: {:?}", action);
-            match action {
-                Action::Quit => return AppReturn::Exit,
-                Action::Sleep => {
-                    if let Some(duration) = self.state.duration().cloned() {
-                        self.dispatch(IoEvent::Sleep(duration)).await
+        if !self.diagnostics.is_empty() {
+            return self.on_diagnostics_key(key);
+        }
+        if self.edit_field(|field| field.on_key(key)) == Some(true) {
+            return AppReturn::Continue;
+        }
+        if !self.modals.is_empty() {
+            return self.on_modal_key(key).await;
+        }
+        if self.palette.is_some() {
+            return self.on_palette_key(key).await;
+        }
+        if self.command_line.is_open() {
+            return self.on_command_key(key).await;
+        }
+        if self.pending.is_empty() {
+            if let Some(screen) = self.screens.last_mut() {
+                match screen.on_key(key, &self.state) {
+                    ScreenKey::Ignored => {}
+                    ScreenKey::Handled => {
+                        self.clear_pending();
+                        return AppReturn::Continue;
+                    }
+                    ScreenKey::Dispatch(events) => {
+                        self.clear_pending();
+                        for event in events {
+                            self.dispatch(event).await;
+                        }
+                        return AppReturn::Continue;
+                    }
+                }
+            }
+        }
+        if let Some(digit) = self.count_digit(key) {
+            self.count = Some((self.count.unwrap_or(0) * 10 + digit).min(MAX_COUNT));
+            self.pending_at = Some(Instant::now());
+            return AppReturn::Continue;
+        }
+        self.pending.push(key);
+        match self.actions.find(&self.pending) {
+            KeyMatch::Action(action) => {
+                let count = self.count;
+                self.clear_pending();
+                self.run_action_count(action, count).await
+            }
+            KeyMatch::Prefix => {
+                self.pending_at = Some(Instant::now());
+                AppReturn::Continue
+            }
+            KeyMatch::None => {
+                warn!("No action found for keys: {}", self.pending_display());
+                self.clear_pending();
+                AppReturn::Continue
+            }
+        }
+    }
+
+    pub fn input_mode(&self) -> InputMode {
+        let editing = match self.modal() {
+            Some(modal) => modal.field().is_some(),
+            None => {
+                self.palette.is_some()
+                    || self.command_line.is_open()
+                    || self
+                        .screen()
+                        .form()
+                        .is_some_and(|form| form.input().is_some())
+            }
+        };
+        if editing {
+            InputMode::Editing
+        } else {
+            InputMode::Normal
+        }
+    }
+
+    /// Edit the focused text field, `None` when there's none
+    ///
+    /// A modal has the focus even without a field, so the fields under it
+    /// can't be edited.
+    fn edit_field<T>(&mut self, edit: impl FnOnce(&mut TextInput) -> T) -> Option<T> {
+        if let Some((modal, _)) = self.modals.front_mut() {
+            return modal.field_mut().map(edit);
+        }
+        if let Some(palette) = self.palette.as_mut() {
+            return Some(palette.edit(edit));
+        }
+        if self.command_line.is_open() {
+            return Some(self.command_line.edit(edit));
+        }
+        self.screens
+            .last_mut()
+            .and_then(|screen| screen.form_mut())
+            .and_then(|form| form.edit(edit))
+    }
+
+    /// Enter dismisses the diagnostics, q or Ctrl+c quits, whatever the keymap says
+    fn on_diagnostics_key(&mut self, key: Key) -> AppReturn {
+        if key == Key::plain(KeyCode::Enter) {
+            self.diagnostics.clear();
+            AppReturn::Continue
+        } else if key == Key::char('q') || key == Key::ctrl('c') {
+            AppReturn::Exit
+        } else {
+            AppReturn::Continue
+        }
+    }
+
+    /// The shown modal gets the keys until it's answered
+    async fn on_modal_key(&mut self, key: Key) -> AppReturn {
+        let answer = self
+            .modals
+            .front_mut()
+            .and_then(|(modal, _)| modal.on_key(key));
+        match answer {
+            Some(answer) => match self.modals.pop_front() {
+                Some((_, reply)) => self.on_answer(reply, answer).await,
+                None => AppReturn::Continue,
+            },
+            None => AppReturn::Continue,
+        }
+    }
+
+    async fn on_answer(&mut self, reply: ModalReply, answer: ModalAnswer) -> AppReturn {
+        debug!("Modal answered: {:?}", answer);
+        match reply {
+            ModalReply::Action(action, count) if answer == ModalAnswer::Confirmed => {
+                self.perform_action(action, count).await
+            }
+            ModalReply::Action(..) => AppReturn::Continue,
+            ModalReply::Command(make_command) => match make_command(answer) {
+                Some(command) => match self.check_command(&command) {
+                    Ok(()) => self.run_command(command).await,
+                    Err(err) => {
+                        warn!("{}", err);
+                        AppReturn::Continue
+                    }
+                },
+                None => AppReturn::Continue,
+            },
+            ModalReply::Channel(tx) => {
+                if tx.send(answer).is_err() {
+                    debug!("Nobody is waiting for the answer any more");
+                }
+                AppReturn::Continue
+            }
+        }
+    }
+
+    /// Show the modal once the ones before are answered
+    pub fn open_modal(&mut self, modal: Modal, reply: ModalReply) {
+        self.clear_pending();
+        self.modals.push_back((modal, reply));
+    }
+
+    /// The shown modal
+    pub fn modal(&self) -> Option<&Modal> {
+        self.modals.front().map(|(modal, _)| modal)
+    }
+
+    /// Ask for the missing argument of the command, then run it
+    fn ask_command_arg(&mut self, command: ArgCommand) {
+        let name = command.name();
+        let title = format!(":{}", name);
+        let values = commands::arg_values(name);
+        if values.is_empty() {
+            let modal = Modal::text(title, format!("Enter {}", commands::usage(name)));
+            let reply = ModalReply::Command(Box::new(move |answer| match answer {
+                ModalAnswer::Text(text) if !text.trim().is_empty() => {
+                    format!("{} {}", name, text.trim()).parse().ok()
+                }
+                _ => None,
+            }));
+            self.open_modal(modal, reply);
+        } else {
+            let modal = Modal::choice(title, "Pick one", values.clone());
+            let reply = ModalReply::Command(Box::new(move |answer| match answer {
+                ModalAnswer::Chosen(index) => {
+                    format!("{} {}", name, values.get(index)?).parse().ok()
+                }
+                _ => None,
+            }));
+            self.open_modal(modal, reply);
+        }
+    }
+
+    /// Edit the query, move the selection, Enter runs the selected action
+    async fn on_palette_key(&mut self, key: Key) -> AppReturn {
+        let palette = match self.palette.as_mut() {
+            Some(palette) => palette,
+            None => return AppReturn::Continue,
+        };
+        let ctrl = key.modifiers == Modifiers::CTRL;
+        match key.code {
+            KeyCode::Esc => self.palette = None,
+            KeyCode::Char('c') if ctrl => self.palette = None,
+            KeyCode::Enter => {
+                let selection = palette.selection();
+                let count = palette.count();
+                self.palette = None;
+                match selection {
+                    Some(action) if self.actions.actions().contains(&action) => {
+                        return self.run_action_count(action, count).await;
+                    }
+                    Some(action) => warn!("{} is not available here", action),
+                    None => debug!("No action matches"),
+                }
+            }
+            KeyCode::Up => palette.select_previous(),
+            KeyCode::Char('p') if ctrl => palette.select_previous(),
+            KeyCode::Down | KeyCode::Tab => palette.select_next(),
+            KeyCode::Char('n') if ctrl => palette.select_next(),
+            _ => {}
+        }
+        AppReturn::Continue
+    }
+
+    /// Complete with Tab, go through history with Up and Down,
+    /// Enter runs the command
+    async fn on_command_key(&mut self, key: Key) -> AppReturn {
+        let ctrl = key.modifiers == Modifiers::CTRL;
+        match key.code {
+            KeyCode::Esc => self.command_line.close(),
+            KeyCode::Char('c') if ctrl => self.command_line.close(),
+            KeyCode::Enter => return self.submit_command().await,
+            KeyCode::Tab => {
+                let item_ids = self
+                    .state
+                    .items()
+                    .iter()
+                    .map(|item| item.id.clone())
+                    .collect::<Vec<_>>();
+                let backward = key.modifiers.contains(Modifiers::SHIFT);
+                self.command_line.complete(backward, &item_ids);
+            }
+            KeyCode::Up => self.command_line.history_previous(),
+            KeyCode::Down => self.command_line.history_next(),
+            // Like vim, erasing the empty line leaves it
+            KeyCode::Backspace if self.command_line.input().is_empty() => self.command_line.close(),
+            _ => {}
+        }
+        AppReturn::Continue
+    }
+
+    /// Run the command line, errors stay on it until it's edited
+    async fn submit_command(&mut self) -> AppReturn {
+        if self.command_line.input().trim().is_empty() {
+            self.command_line.close();
+            return AppReturn::Continue;
+        }
+        let command = self
+            .command_line
+            .submit()
+            .and_then(|command| self.check_command(&command).map(|_| command));
+        match command {
+            Ok(command) => {
+                self.command_line.close();
+                self.run_command(command).await
+            }
+            Err(err) => {
+                debug!("Command failed: {}", err);
+                self.command_line.set_error(err.to_string());
+                AppReturn::Continue
+            }
+        }
+    }
+
+    /// Commands can only do what the current actions allow
+    fn check_command(&self, command: &Command) -> Result<(), CommandError> {
+        let available = command
+            .action()
+            .is_none_or(|action| self.actions.actions().contains(&action));
+        if available {
+            Ok(())
+        } else {
+            Err(CommandError::Unavailable(command.name().to_string()))
+        }
+    }
+
+    pub async fn run_command(&mut self, command: Command) -> AppReturn {
+        debug!("Running command: {:?}", command);
+        match command {
+            Command::Action(action, count) => return self.run_action_count(action, count).await,
+            Command::Sleep(duration) => {
+                self.dispatch(IoEvent::Sleep(duration)).await;
+            }
+            Command::Delay(secs) => self.state.set_delay(secs),
+            Command::LogLevel(level) => {
+                logging::set_level(level);
+                info!("Log level set to {}", level);
+            }
+            Command::FetchItem(id) => {
+                self.dispatch(IoEvent::FetchItem(id)).await;
+            }
+            Command::Ask(command) => self.ask_command_arg(command),
+        }
+        AppReturn::Continue
+    }
+
+    pub fn command_line(&self) -> &CommandLine {
+        &self.command_line
+    }
+
+    /// The open command palette
+    pub fn palette(&self) -> Option<&Palette> {
+        self.palette.as_ref()
+    }
+
+    /// Report a configuration problem, shown until dismissed
+    pub fn diagnose(&mut self, problem: String) {
+        if !self.diagnostics.contains(&problem) {
+            self.diagnostics.push(problem);
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[String] {
+        &self.diagnostics
+    }
+
+    /// The shown screen
+    pub fn screen(&self) -> &dyn Screen {
+        self.screens
+            .last()
+            .map(Box::as_ref)
+            .expect("the navigation stack is never empty")
+    }
+
+    /// Draw the shown screen, it can keep what drawing works out
+    pub fn draw_screen(&mut self, area: Rect, buf: &mut Buffer) {
+        let context = ScreenContext {
+            state: &self.state,
+            auth: &self.auth,
+            retry: self.retry.as_ref().map(|(_, notice)| notice),
+            loading: !self.jobs.is_idle(),
+        };
+        self.screens
+            .last_mut()
+            .expect("the navigation stack is never empty")
+            .draw(&context, area, buf);
+    }
+
+    /// Navigation stack, from the first screen to the shown one
+    pub fn screens(&self) -> impl Iterator<Item = &dyn Screen> {
+        self.screens.iter().map(Box::as_ref)
+    }
+
+    /// Show the screen, `Back` returns to the current one
+    pub fn push_screen(&mut self, screen: Box<dyn Screen>) {
+        debug!("Showing screen {:?}", screen.id());
+        self.screens.push(screen);
+        self.on_navigation();
+    }
+
+    /// Back to the previous screen, the first one stays
+    pub fn pop_screen(&mut self) {
+        if self.screens.len() > 1 {
+            self.screens.pop();
+            self.on_navigation();
+        }
+    }
+
+    /// Show the screen in place of the current one
+    pub fn replace_screen(&mut self, screen: Box<dyn Screen>) {
+        debug!("Replacing screen with {:?}", screen.id());
+        self.screens.pop();
+        self.screens.push(screen);
+        self.on_navigation();
+    }
+
+    /// Show the screen as the only one, with nothing to go back to
+    pub fn reset_screen(&mut self, screen: Box<dyn Screen>) {
+        debug!("Resetting screens to {:?}", screen.id());
+        self.screens = vec![screen];
+        self.on_navigation();
+    }
+
+    fn on_navigation(&mut self) {
+        self.clear_pending();
+        self.refresh_actions();
+    }
+
+    /// Global actions, around the ones of the shown screen
+    fn refresh_actions(&mut self) {
+        let mut actions = vec![Action::Quit, Action::Suspend];
+        actions.extend(self.screen().actions(self));
+        if self.screens.len() > 1 {
+            actions.push(Action::Back);
+        }
+        actions.extend([
+            Action::CancelJob,
+            Action::Repeat,
+            Action::CommandPalette,
+            Action::CommandLine,
+        ]);
+        self.set_actions(actions);
+    }
+
+    /// Update contextual actions, leaving out (and reporting) those whose keys conflict
+    fn set_actions(&mut self, actions: Vec<Action>) {
+        let screen = self.screen();
+        self.actions = match Actions::for_screen(actions.clone(), &screen.keys(), &screen.title()) {
+            Ok(actions) => actions,
+            Err(err) => {
+                error!("{}", err);
+                let conflicting = err.actions();
+                self.diagnose(err.to_string());
+                actions
+                    .into_iter()
+                    .filter(|action| !conflicting.contains(action))
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .unwrap_or_default()
+            }
+        };
+    }
+
+    /// The digit, if the key continues a count: 0 can't start one,
+    /// and bound digits run their action
+    fn count_digit(&self, key: Key) -> Option<u32> {
+        let digit = match key.code {
+            KeyCode::Char(c) if key.modifiers.is_empty() => c.to_digit(10)?,
+            _ => return None,
+        };
+        let counting = digit != 0 || self.count.is_some();
+        let bound = self.actions.find(&[key]) != KeyMatch::None;
+        (self.pending.is_empty() && counting && !bound).then_some(digit)
+    }
+
+    /// Keys of an unfinished sequence
+    pub fn pending_keys(&self) -> &[Key] {
+        &self.pending
+    }
+
+    /// Count typed so far
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+
+    /// The pending keys as they're shown, e.g. `<g> <g>`
+    fn pending_display(&self) -> String {
+        self.pending
+            .iter()
+            .map(Key::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn clear_pending(&mut self) {
+        self.pending.clear();
+        self.pending_at = None;
+        self.count = None;
+    }
+
+    /// Run the action, whatever triggered it
+    pub async fn run_action(&mut self, action: Action) -> AppReturn {
+        self.run_action_count(action, None).await
+    }
+
+    /// Run the action as many times as the count says, if it accepts one
+    pub async fn run_action_count(&mut self, action: Action, count: Option<u32>) -> AppReturn {
+        // A count given to repeat replaces the one of the repeated action
+        let (action, count) = match (action, self.last_action) {
+            (Action::Repeat, Some((last, last_count)))
+                if self.actions.actions().contains(&last) =>
+            {
+                (last, count.or(last_count))
+            }
+            (Action::Repeat, Some((last, _))) => {
+                debug!("Can't repeat {} here", last);
+                return AppReturn::Continue;
+            }
+            (Action::Repeat, None) => {
+                debug!("Nothing to repeat");
+                return AppReturn::Continue;
+            }
+            _ => (action, count),
+        };
+        if let Some(question) = self.confirmation(action) {
+            let modal = Modal::confirm(action.to_string(), question);
+            self.open_modal(modal, ModalReply::Action(action, count));
+            return AppReturn::Continue;
+        }
+        self.perform_action(action, count).await
+    }
+
+    /// Question to answer before running the action, if it needs one
+    fn confirmation(&self, action: Action) -> Option<String> {
+        match action {
+            Action::Quit => {
+                let running = self.jobs.in_flight().count();
+                (running > 0).then(|| match running {
+                    1 => String::from("1 job is still running, quit anyway?"),
+                    _ => format!("{} jobs are still running, quit anyway?", running),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Run the action, confirmed if it needed to be
+    async fn perform_action(&mut self, action: Action, count: Option<u32>) -> AppReturn {
+        if action.is_repeatable() {
+            self.last_action = Some((action, count));
+        }
+        let times = match count {
+            Some(count) if action.accepts_count() => count,
+            Some(count) => {
+                debug!("{} takes no count, ignoring {}", action, count);
+                1
+            }
+            None => 1,
+        };
+
+        debug!("Doing action: {:?} x{}", action, times);
+        match action {
+            Action::Quit => AppReturn::Exit,
+            Action::Suspend => AppReturn::Suspend,
+            Action::Sleep => {
+                if let Some(duration) = self.state.duration().cloned() {
+                    for _ in 0..times {
+                        self.dispatch(IoEvent::Sleep(duration)).await;
                     }
-                    AppReturn::Continue
                 }
-                Action::IncrementDelay => {
+                AppReturn::Continue
+            }
+            Action::IncrementDelay => {
+                for _ in 0..times {
                     self.state.increment_delay();
-                    AppReturn::Continue
                 }
-                Action::DecrementDelay => {
+                AppReturn::Continue
+            }
+            Action::DecrementDelay => {
+                for _ in 0..times {
                     self.state.decrement_delay();
-                    AppReturn::Continue
                 }
+                AppReturn::Continue
             }
-        } else {
-            warn!("No action found for key: {:?}", key);
-            AppReturn::Continue
+            Action::FetchItems => {
+                self.dispatch(IoEvent::FetchItems).await;
+                AppReturn::Continue
+            }
+            Action::CancelJob => {
+                self.cancel_last_job();
+                AppReturn::Continue
+            }
+            Action::Login => {
+                self.auth = AuthStatus::LoggingIn;
+                self.dispatch(IoEvent::Login).await;
+                AppReturn::Continue
+            }
+            // Resolved to the last action above
+            Action::Repeat => AppReturn::Continue,
+            Action::CommandPalette => {
+                self.palette = Some(Palette::new(count));
+                AppReturn::Continue
+            }
+            Action::CommandLine => {
+                self.command_line.open();
+                AppReturn::Continue
+            }
+            Action::Back => {
+                self.pop_screen();
+                AppReturn::Continue
+            }
+            Action::ShowItems => {
+                if self.screen().id() != ScreenId::Items {
+                    self.push_screen(Box::new(Items::new()));
+                }
+                AppReturn::Continue
+            }
+            Action::Settings => {
+                if self.screen().id() != ScreenId::Settings {
+                    let settings = SettingsForm::new(self);
+                    self.push_screen(Box::new(settings));
+                }
+                AppReturn::Continue
+            }
+        }
+    }
+
+    pub fn on_resize(&mut self, width: u16, height: u16) {
+        debug!("Terminal resized to {}x{}", width, height);
+        self.size = Rect::new(0, 0, width, height);
+    }
+
+    pub async fn on_mouse(&mut self, mouse: MouseEvent) -> AppReturn {
+        let covered = !self.diagnostics.is_empty()
+            || !self.modals.is_empty()
+            || self.palette.is_some()
+            || self.command_line.is_open();
+        let areas = match ui::areas(self.size) {
+            Some(areas) if !covered => areas,
+            _ => return AppReturn::Continue,
+        };
+        let point = Rect::new(mouse.column, mouse.row, 1, 1);
+        let help = match areas.help.filter(|help| help.intersects(point)) {
+            Some(help) => help,
+            None => {
+                if let Some(screen) = self.screens.last_mut() {
+                    screen.on_mouse(mouse, &self.state);
+                }
+                return AppReturn::Continue;
+            }
+        };
+        match mouse.kind {
+            MouseEventKind::ScrollDown => ui::scroll_help(&mut self.help, &self.actions, help, 1),
+            MouseEventKind::ScrollUp => ui::scroll_help(&mut self.help, &self.actions, help, -1),
+            // Clicking a help row runs its action
+            MouseEventKind::Down(MouseButton::Left) => {
+                let action =
+                    ui::help_action_at(&self.help, &self.actions, help, mouse.column, mouse.row);
+                if let Some(action) = action {
+                    return self.run_action(action).await;
+                }
+            }
+            _ => {}
+        }
+        AppReturn::Continue
+    }
+
+    /// Help pane and how far it's scrolled
+    pub fn help(&self) -> &TableView {
+        &self.help
+    }
+
+    pub fn on_paste(&mut self, text: String) {
+        if self.edit_field(|field| field.paste(&text)).is_none() {
+            debug!("Ignoring pasted text ({} chars)", text.chars().count());
         }
     }
 
+    pub fn on_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn handle_event(&mut self, event: AppEvent) -> AppReturn {
+        match event {
+            AppEvent::Initialized => self.initialized(),
+            AppEvent::LoggedIn => self.logged_in(),
+            AppEvent::NeedsLogin(reason) => self.needs_login(reason),
+            AppEvent::LoginFailed(reason) => self.login_failed(reason),
+            AppEvent::Slept => self.slept(),
+            AppEvent::ItemsLoaded(items) => self.items_loaded(items),
+            AppEvent::ItemLoaded(item) => self.item_loaded(item),
+            AppEvent::ApiFailed(error) => self.api_failed(error),
+            AppEvent::Retrying(id, notice) => self.retrying(id, notice),
+            AppEvent::JobSpawned(id, abort) => self.job_spawned(id, abort),
+            AppEvent::JobStarted(id) => self.job_started(id),
+            AppEvent::JobFinished(id, status) => self.job_finished(id, status),
+            AppEvent::Ask(modal, tx) => self.open_modal(modal, ModalReply::Channel(tx)),
+            AppEvent::ApiConfigured(url) => self.api_url = url,
+            AppEvent::SettingsUpdated(settings) => self.settings_updated(settings),
+        }
+        AppReturn::Continue
+    }
+
     pub fn update_on_tick(&mut self) -> AppReturn {
         self.state.incr_tick();
+        if self
+            .pending_at
+            .is_some_and(|at| at.elapsed() >= SEQUENCE_TIMEOUT)
+        {
+            debug!(
+                "Key sequence timed out: {} {}",
+                self.count
+                    .map(|count| count.to_string())
+                    .unwrap_or_default(),
+                self.pending_display()
+            );
+            self.clear_pending();
+        }
         AppReturn::Continue
     }
 
@@ -83,27 +843,126 @@ impl App {
         &self.state
     }
 
+    pub fn auth(&self) -> &AuthStatus {
+        &self.auth
+    }
+
+    /// Set while a request is being retried
+    pub fn retry(&self) -> Option<&RetryNotice> {
+        self.retry.as_ref().map(|(_, notice)| notice)
+    }
+
+    /// Base URL of the API, `None` when it's disabled
+    pub fn api_url(&self) -> Option<&Url> {
+        self.api_url.as_ref()
+    }
+
+    /// Whether the terminal has the focus
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn jobs(&self) -> &Jobs {
+        &self.jobs
+    }
+
     pub fn is_loading(&self) -> bool {
-        self.is_loading
+        !self.jobs.is_idle()
     }
 
     pub fn initialized(&mut self) {
-        // Update contextual actions
-        self.actions = vec![
-            Action::Quit,
-            Action::Sleep,
-            Action::IncrementDelay,
-            Action::DecrementDelay,
-        ]
-        .into();
-        self.state = AppState::initialized()
+        self.state = AppState::initialized();
+        if self.screen().id() == ScreenId::Login {
+            self.reset_screen(Box::new(Dashboard));
+        } else {
+            // Update contextual actions
+            self.clear_pending();
+            self.refresh_actions();
+        }
     }
 
-    pub fn loaded(&mut self) {
-        self.is_loading = false;
+    /// The job task exists and can be cancelled
+    pub fn job_spawned(&mut self, id: JobId, abort: AbortHandle) {
+        self.jobs.set_abort_handle(id, abort);
+    }
+
+    pub fn job_started(&mut self, id: JobId) {
+        self.jobs.start(id);
+    }
+
+    pub fn job_finished(&mut self, id: JobId, status: JobStatus) {
+        let cancelled_login = self.jobs.finish(id, status).is_some_and(|job| {
+            job.status() == &JobStatus::Cancelled && matches!(job.event(), IoEvent::Login)
+        });
+        if cancelled_login {
+            self.auth = AuthStatus::LoggedOut;
+        }
+        // Whatever the outcome, it's not retrying anymore
+        if self.retry.as_ref().is_some_and(|(job, _)| *job == id) {
+            self.retry = None;
+        }
+    }
+
+    /// Cancel the most recently dispatched job still in flight
+    pub fn cancel_last_job(&mut self) {
+        let last = self.jobs.in_flight().next_back().map(|job| job.id());
+        match last {
+            Some(id) => {
+                if self.jobs.cancel(id) {
+                    warn!("Cancelling job {}", id);
+                }
+            }
+            None => debug!("No job to cancel"),
+        }
+    }
+
+    /// The settings form is done once they're applied
+    pub fn settings_updated(&mut self, settings: Settings) {
+        self.state.set_delay(settings.delay);
+        self.api_url = settings.api_url;
+        if self.screen().id() == ScreenId::Settings {
+            self.pop_screen();
+        }
     }
 
     pub fn slept(&mut self) {
         self.state.incr_sleep();
     }
+
+    pub fn needs_login(&mut self, reason: String) {
+        self.state = AppState::needs_login(reason);
+        self.auth = AuthStatus::LoggedOut;
+        self.reset_screen(Box::new(Login));
+    }
+
+    pub fn logged_in(&mut self) {
+        if !self.state.is_initialized() {
+            self.initialized();
+        }
+        self.auth = AuthStatus::LoggedIn;
+    }
+
+    pub fn login_failed(&mut self, reason: String) {
+        self.auth = AuthStatus::Failed(reason);
+    }
+
+    pub fn retrying(&mut self, id: JobId, notice: RetryNotice) {
+        self.retry = Some((id, notice));
+    }
+
+    pub fn items_loaded(&mut self, items: Vec<Item>) {
+        self.state.set_items(items);
+    }
+
+    pub fn item_loaded(&mut self, item: Item) {
+        self.state.set_item(item);
+    }
+
+    pub fn api_failed(&mut self, error: ApiError) {
+        if let ApiError::Unauthenticated(reason) = &error {
+            self.needs_login(reason.clone());
+        } else {
+            self.state.set_api_error(error);
+        }
+    }
 }
@@ -0,0 +1,180 @@
+use std::fmt::{self, Debug, Formatter};
+
+use tokio::sync::oneshot;
+
+use crate::inputs::key::{Key, KeyCode, Modifiers};
+
+use super::{actions::Action, command::Command, input::TextInput};
+
+/// Dialog over everything else, it gets the keys until answered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Modal {
+    title: String,
+    message: String,
+    kind: ModalKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModalKind {
+    /// Yes or no, no is selected first
+    Confirm { yes: bool },
+    /// One of the options
+    Choice {
+        options: Vec<String>,
+        selected: usize,
+    },
+    /// A line of text
+    Text { input: TextInput },
+}
+
+/// How a modal was closed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModalAnswer {
+    Confirmed,
+    /// Index of the chosen option
+    Chosen(usize),
+    Text(String),
+    /// Escaped, or answered no
+    Cancelled,
+}
+
+/// Where the answer goes
+pub enum ModalReply {
+    /// Run the action, it was confirmed
+    Action(Action, Option<u32>),
+    /// Run the command made from the answer, if any
+    Command(Box<dyn FnOnce(ModalAnswer) -> Option<Command> + Send>),
+    /// Hand the answer to the IO task waiting for it
+    Channel(oneshot::Sender<ModalAnswer>),
+}
+
+impl Debug for ModalReply {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ModalReply::Action(action, count) => write!(f, "Action({:?}, {:?})", action, count),
+            ModalReply::Command(_) => write!(f, "Command(..)"),
+            ModalReply::Channel(_) => write!(f, "Channel(..)"),
+        }
+    }
+}
+
+impl Modal {
+    /// Ask yes or no
+    pub fn confirm(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(title, message, ModalKind::Confirm { yes: false })
+    }
+
+    /// Ask to pick one of the options
+    pub fn choice(
+        title: impl Into<String>,
+        message: impl Into<String>,
+        options: Vec<String>,
+    ) -> Self {
+        let kind = ModalKind::Choice {
+            options,
+            selected: 0,
+        };
+        Self::new(title, message, kind)
+    }
+
+    /// Ask for a line of text
+    pub fn text(title: impl Into<String>, message: impl Into<String>) -> Self {
+        let kind = ModalKind::Text {
+            input: TextInput::new(),
+        };
+        Self::new(title, message, kind)
+    }
+
+    /// Ask for a line of text that isn't shown, like a token
+    pub fn secret(title: impl Into<String>, message: impl Into<String>) -> Self {
+        let kind = ModalKind::Text {
+            input: TextInput::masked(),
+        };
+        Self::new(title, message, kind)
+    }
+
+    fn new(title: impl Into<String>, message: impl Into<String>, kind: ModalKind) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            kind,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn kind(&self) -> &ModalKind {
+        &self.kind
+    }
+
+    /// The text being typed, for text prompts
+    pub fn field(&self) -> Option<&TextInput> {
+        match &self.kind {
+            ModalKind::Text { input } => Some(input),
+            _ => None,
+        }
+    }
+
+    pub fn field_mut(&mut self) -> Option<&mut TextInput> {
+        match &mut self.kind {
+            ModalKind::Text { input } => Some(input),
+            _ => None,
+        }
+    }
+
+    /// The answer, once the key closes the modal
+    ///
+    /// Esc and Ctrl+c cancel any modal, Enter answers with the selection.
+    pub fn on_key(&mut self, key: Key) -> Option<ModalAnswer> {
+        if key == Key::plain(KeyCode::Esc) || key == Key::ctrl('c') {
+            return Some(ModalAnswer::Cancelled);
+        }
+        let plain = key.modifiers == Modifiers::NONE;
+        match &mut self.kind {
+            ModalKind::Confirm { yes } => match key.code {
+                KeyCode::Enter if *yes => Some(ModalAnswer::Confirmed),
+                KeyCode::Enter => Some(ModalAnswer::Cancelled),
+                KeyCode::Char('y' | 'Y') if plain => Some(ModalAnswer::Confirmed),
+                KeyCode::Char('n' | 'N') if plain => Some(ModalAnswer::Cancelled),
+                KeyCode::Left | KeyCode::Right | KeyCode::Tab => {
+                    *yes = !*yes;
+                    None
+                }
+                _ => None,
+            },
+            ModalKind::Choice { options, selected } => match key.code {
+                KeyCode::Enter if !options.is_empty() => Some(ModalAnswer::Chosen(*selected)),
+                KeyCode::Up | KeyCode::Char('k') if *selected > 0 => {
+                    *selected -= 1;
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab
+                    if *selected + 1 < options.len() =>
+                {
+                    *selected += 1;
+                    None
+                }
+                // 1 to 9 pick an option directly
+                KeyCode::Char(c) if plain => c
+                    .to_digit(10)
+                    .and_then(|digit| (digit as usize).checked_sub(1))
+                    .filter(|index| *index < options.len())
+                    .map(ModalAnswer::Chosen),
+                _ => None,
+            },
+            ModalKind::Text { input } => match key.code {
+                KeyCode::Enter => Some(ModalAnswer::Text(input.value().to_string())),
+                _ => {
+                    input.on_key(key);
+                    None
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use super::{actions::Action, input::TextInput};
+
+/// Command palette, every action searchable by name
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    query: TextInput,
+    selected: usize,
+    /// Count typed before opening, given to the chosen action
+    count: Option<u32>,
+}
+
+impl Palette {
+    pub fn new(count: Option<u32>) -> Self {
+        Self {
+            count,
+            ..Self::default()
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        self.query.value()
+    }
+
+    pub fn field(&self) -> &TextInput {
+        &self.query
+    }
+
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+
+    /// Index of the selected action in `matches`
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Actions matching the query, best first
+    pub fn matches(&self) -> Vec<Action> {
+        let mut matches = Action::iterator()
+            .filter(|action| **action != Action::CommandPalette)
+            .filter_map(|action| {
+                Some((
+                    *action,
+                    fuzzy_score(self.query.value(), &action.to_string())?,
+                ))
+            })
+            .collect::<Vec<_>>();
+        // Stable, ties keep the actions order
+        matches.sort_by_key(|(_, score)| -score);
+        matches.into_iter().map(|(action, _)| action).collect()
+    }
+
+    pub fn selection(&self) -> Option<Action> {
+        self.matches().get(self.selected).copied()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.edit(|query| query.insert(c));
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.edit(|query| query.paste(s));
+    }
+
+    pub fn pop(&mut self) {
+        self.edit(TextInput::backspace);
+    }
+
+    /// Change the query, a new query starts from the best match
+    pub fn edit<T>(&mut self, edit: impl FnOnce(&mut TextInput) -> T) -> T {
+        let before = self.query.value().to_string();
+        let result = edit(&mut self.query);
+        if self.query.value() != before {
+            self.selected = 0;
+        }
+        result
+    }
+
+    /// Move the selection down, wrapping around
+    pub fn select_next(&mut self) {
+        let count = self.matches().len();
+        if count > 0 {
+            self.selected = (self.selected + 1) % count;
+        }
+    }
+
+    /// Move the selection up, wrapping around
+    pub fn select_previous(&mut self) {
+        let count = self.matches().len();
+        if count > 0 {
+            self.selected = (self.selected + count - 1) % count;
+        }
+    }
+}
+
+/// How well `query` matches `text` as a subsequence, ignoring case
+///
+/// Higher is better, matches in a row and at word starts score more,
+/// and `None` if some query char can't be found.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text = text.chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = (next..text.len()).find(|i| chars_match(q, text[*i]))?;
+        score += 1;
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        match previous {
+            Some(previous) if previous + 1 == found => score += 5,
+            // Gaps cost a little, so tighter matches go first
+            Some(previous) => score -= (found - previous - 1) as i64,
+            None => score -= found as i64,
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+fn chars_match(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
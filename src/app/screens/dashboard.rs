@@ -0,0 +1,103 @@
+use tui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, Paragraph, Widget},
+};
+
+use crate::app::{actions::Action, state::AuthStatus, App};
+
+use super::{Screen, ScreenContext, ScreenId};
+
+/// Home screen, the sleep demo and a summary of everything else
+#[derive(Debug, Clone, Default)]
+pub struct Dashboard;
+
+impl Screen for Dashboard {
+    fn id(&self) -> ScreenId {
+        ScreenId::Dashboard
+    }
+
+    fn title(&self) -> String {
+        String::from("Dashboard")
+    }
+
+    fn actions(&self, app: &App) -> Vec<Action> {
+        if !app.state().is_initialized() {
+            return vec![];
+        }
+        vec![
+            Action::Sleep,
+            Action::IncrementDelay,
+            Action::DecrementDelay,
+            Action::Login,
+            Action::FetchItems,
+            Action::ShowItems,
+            Action::Settings,
+        ]
+    }
+
+    fn draw(&mut self, context: &ScreenContext, area: Rect, buf: &mut Buffer) {
+        draw_body(context).render(area, buf);
+    }
+}
+
+fn draw_body<'a>(context: &ScreenContext) -> Paragraph<'a> {
+    let state = context.state;
+    let initialized_text = if state.is_initialized() {
+        String::from("Initialized")
+    } else if let Some(reason) = state.login_reason() {
+        format!("Login required: {}", reason)
+    } else {
+        String::from("Not Initialized !")
+    };
+    let loading_text = match context.retry {
+        Some(notice) => notice.to_string(),
+        None if context.loading => String::from("Loading..."),
+        None => String::default(),
+    };
+    let sleep_text = if let Some(sleeps) = state.count_sleep() {
+        format!("Sleep count: {}", sleeps)
+    } else {
+        String::default()
+    };
+    let tick_text = if let Some(ticks) = state.count_tick() {
+        format!("Tick count: {}", ticks)
+    } else {
+        String::default()
+    };
+    let items_text = if let Some(err) = state.api_error() {
+        format!("API error: {}", err)
+    } else if let Some(item) = state.item() {
+        format!("Item: {} ({} items)", item.name, state.items().len())
+    } else if state.is_initialized() {
+        format!("Items: {}", state.items().len())
+    } else {
+        String::default()
+    };
+    let auth_text = match context.auth {
+        AuthStatus::LoggedOut => String::from("Not logged in"),
+        AuthStatus::LoggingIn => String::from("Logging in, check your browser..."),
+        AuthStatus::LoggedIn => String::from("Logged in"),
+        AuthStatus::Failed(reason) => format!("Login failed: {}", reason),
+    };
+
+    Paragraph::new(vec![
+        Spans::from(Span::raw(initialized_text)),
+        Spans::from(Span::raw(loading_text)),
+        Spans::from(Span::raw(sleep_text)),
+        Spans::from(Span::raw(tick_text)),
+        Spans::from(Span::raw(auth_text)),
+        Spans::from(Span::raw(items_text)),
+    ])
+    .style(Style::default().fg(Color::LightCyan))
+    .alignment(Alignment::Left)
+    .block(
+        Block::default()
+            // .title("Body")
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::White))
+            .border_type(BorderType::Plain),
+    )
+}
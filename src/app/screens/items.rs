@@ -0,0 +1,199 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    api::models::Item,
+    app::{
+        actions::Action,
+        state::AppState,
+        table::{TableRow, TableView},
+        ui, App,
+    },
+    inputs::key::{Key, KeyCode},
+    io::IoEvent,
+};
+
+use super::{Screen, ScreenContext, ScreenId, ScreenKey};
+
+/// Items fetched from the API, Enter fetches the marked ones
+/// (or the selected one) to show it in full
+#[derive(Debug, Clone)]
+pub struct Items {
+    table: TableView,
+    /// Where the table was last drawn, its first row shown and how many fit
+    area: Rect,
+    top: usize,
+    page: usize,
+}
+
+impl Items {
+    pub fn new() -> Self {
+        Self {
+            table: TableView::new(&[("ID", 12), ("Name", 24), ("Description", 40)]),
+            area: Rect::default(),
+            top: 0,
+            page: 1,
+        }
+    }
+
+    pub fn table(&self) -> &TableView {
+        &self.table
+    }
+}
+
+impl Default for Items {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TableRow for Item {
+    fn key(&self) -> String {
+        self.id.clone()
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.id.clone(),
+            self.name.clone(),
+            self.description.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+impl Screen for Items {
+    fn id(&self) -> ScreenId {
+        ScreenId::Items
+    }
+
+    fn title(&self) -> String {
+        String::from("Items")
+    }
+
+    fn actions(&self, _app: &App) -> Vec<Action> {
+        vec![Action::FetchItems]
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        let mut keys = self.table.keys();
+        keys.push(Key::plain(KeyCode::Enter));
+        keys
+    }
+
+    fn on_key(&mut self, key: Key, state: &AppState) -> ScreenKey {
+        let items = state.items();
+        if key == Key::plain(KeyCode::Enter) {
+            let mut marked = self.table.marked(items);
+            if marked.is_empty() {
+                marked.extend(self.table.selection(items));
+            }
+            return ScreenKey::Dispatch(
+                marked
+                    .into_iter()
+                    .map(|item| IoEvent::FetchItem(item.id.clone()))
+                    .collect(),
+            );
+        }
+        if self.table.on_key(key, items, self.page) {
+            ScreenKey::Handled
+        } else {
+            ScreenKey::Ignored
+        }
+    }
+
+    /// The wheel moves the selection, a click selects the row
+    fn on_mouse(&mut self, mouse: MouseEvent, state: &AppState) {
+        let items = state.items();
+        if !self
+            .area
+            .intersects(Rect::new(mouse.column, mouse.row, 1, 1))
+        {
+            return;
+        }
+        let selected = self.table.selected();
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.table.select(items.len(), selected + 1),
+            MouseEventKind::ScrollUp => self.table.select(items.len(), selected.saturating_sub(1)),
+            MouseEventKind::Down(MouseButton::Left) => {
+                // Below the border and the header
+                let shown = self.area.y + 2..self.area.y + 2 + self.page as u16;
+                if shown.contains(&mouse.row) {
+                    let row = self.top + (mouse.row - shown.start) as usize;
+                    if row < items.len() {
+                        self.table.select(items.len(), row);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self, context: &ScreenContext, area: Rect, buf: &mut Buffer) {
+        let state = context.state;
+        let message = if let Some(err) = state.api_error() {
+            Some(Span::styled(
+                format!("API error: {}", err),
+                Style::default().fg(Color::Red),
+            ))
+        } else if state.items().is_empty() {
+            Some(Span::raw("No items, fetch them first"))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            self.area = Rect::default();
+            return Paragraph::new(Spans::from(message))
+                .style(Style::default().fg(Color::LightCyan))
+                .block(block(String::from("0 items")))
+                .render(area, buf);
+        }
+
+        let (table_area, item_area) = match state.item() {
+            Some(_) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(4), Constraint::Length(5)])
+                    .split(area);
+                (chunks[0], Some(chunks[1]))
+            }
+            None => (area, None),
+        };
+
+        let items = state.items();
+        let marked = self.table.marked(items).len();
+        let title = match marked {
+            0 => format!("{} items", items.len()),
+            marked => format!("{} items, {} marked", items.len(), marked),
+        };
+        self.area = table_area;
+        self.page = ui::draw_table(&self.table, &mut self.top, items, title, table_area, buf);
+
+        if let (Some(item), Some(area)) = (state.item(), item_area) {
+            let description = item.description.as_deref().unwrap_or("No description");
+            Paragraph::new(vec![
+                Spans::from(Span::styled(
+                    item.name.clone(),
+                    Style::default().fg(Color::LightCyan),
+                )),
+                Spans::from(Span::raw(description)),
+            ])
+            .wrap(Wrap { trim: true })
+            .block(block(format!("Item {}", item.id)))
+            .render(area, buf);
+        }
+    }
+}
+
+fn block<'a>(title: String) -> Block<'a> {
+    Block::default()
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White))
+        .border_type(BorderType::Plain)
+        .title(title)
+}
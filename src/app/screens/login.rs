@@ -0,0 +1,60 @@
+use tui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, Paragraph, Widget, Wrap},
+};
+
+use crate::app::{actions::Action, state::AuthStatus, App};
+
+use super::{Screen, ScreenContext, ScreenId};
+
+/// No usable session, only logging in is possible
+#[derive(Debug, Clone, Default)]
+pub struct Login;
+
+impl Screen for Login {
+    fn id(&self) -> ScreenId {
+        ScreenId::Login
+    }
+
+    fn title(&self) -> String {
+        String::from("Login")
+    }
+
+    fn actions(&self, _app: &App) -> Vec<Action> {
+        vec![Action::Login]
+    }
+
+    fn draw(&mut self, context: &ScreenContext, area: Rect, buf: &mut Buffer) {
+        let reason = context.state.login_reason().unwrap_or("Not logged in");
+        let status = match context.auth {
+            AuthStatus::LoggingIn => String::from("Logging in, check your browser..."),
+            AuthStatus::Failed(reason) => format!("Login failed: {}", reason),
+            AuthStatus::LoggedOut | AuthStatus::LoggedIn => String::default(),
+        };
+        let login_keys = Action::Login
+            .keys()
+            .first()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| String::from(":login"));
+
+        Paragraph::new(vec![
+            Spans::from(Span::raw(format!("Login required: {}", reason))),
+            Spans::from(Span::raw("")),
+            Spans::from(Span::raw(format!("Press {} to log in", login_keys))),
+            Spans::from(Span::raw(status)),
+        ])
+        .style(Style::default().fg(Color::LightCyan))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::White))
+                .border_type(BorderType::Plain),
+        )
+        .render(area, buf);
+    }
+}
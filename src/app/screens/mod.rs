@@ -0,0 +1,99 @@
+use crossterm::event::MouseEvent;
+use tui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+use crate::{inputs::key::Key, io::IoEvent};
+
+use crate::api::retry::RetryNotice;
+
+use super::{
+    actions::Action,
+    form::Form,
+    state::{AppState, AuthStatus},
+    App,
+};
+
+pub mod dashboard;
+pub mod items;
+pub mod login;
+pub mod settings;
+
+pub use self::{dashboard::Dashboard, items::Items, login::Login, settings::SettingsForm};
+
+/// Identifies a screen on the navigation stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenId {
+    Dashboard,
+    Login,
+    Items,
+    Settings,
+}
+
+/// What a key did on the screen
+#[derive(Debug, Clone)]
+pub enum ScreenKey {
+    /// Not for the screen, the keymap can have it
+    Ignored,
+    Handled,
+    /// Jobs to start
+    Dispatch(Vec<IoEvent>),
+}
+
+/// One page of the app: what it shows and the actions it offers
+///
+/// Screens draw into the body area, the title, help, jobs and overlays
+/// around it are the same everywhere.
+pub trait Screen {
+    fn id(&self) -> ScreenId;
+
+    /// Shown in the title bar
+    fn title(&self) -> String;
+
+    /// Actions of this screen, on top of the global ones
+    fn actions(&self, app: &App) -> Vec<Action>;
+
+    /// Form of the screen, its focused field gets the text typed
+    fn form(&self) -> Option<&Form> {
+        None
+    }
+
+    fn form_mut(&mut self) -> Option<&mut Form> {
+        None
+    }
+
+    /// Keys `on_key` handles, bindings starting with one of them are conflicts
+    fn keys(&self) -> Vec<Key> {
+        vec![]
+    }
+
+    /// Keys for the screen itself, before the keymap
+    fn on_key(&mut self, _key: Key, _state: &AppState) -> ScreenKey {
+        ScreenKey::Ignored
+    }
+
+    /// Mouse events outside the help, the screen finds what's under them
+    /// from where it was last drawn
+    fn on_mouse(&mut self, _mouse: MouseEvent, _state: &AppState) {}
+
+    /// Mutable to keep what drawing works out, like a scroll position
+    fn draw(&mut self, context: &ScreenContext, area: Rect, buf: &mut Buffer);
+}
+
+/// What screens are drawn from, the parts of the `App` besides its screens
+pub struct ScreenContext<'a> {
+    pub state: &'a AppState,
+    pub auth: &'a AuthStatus,
+    pub retry: Option<&'a RetryNotice>,
+    /// Some job is pending or running
+    pub loading: bool,
+}
+
+/// Renders the shown screen as a widget, so it can go through `Frame::render_widget`
+pub struct ScreenWidget<'a> {
+    pub app: &'a mut App,
+}
+
+impl Widget for ScreenWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.app.draw_screen(area, buf);
+    }
+}
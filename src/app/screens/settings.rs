@@ -0,0 +1,88 @@
+use tui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use url::Url;
+
+use crate::{
+    app::{
+        actions::Action,
+        form::{self, Field, Form, FormKey, FormValues},
+        state::{AppState, MAX_DELAY, MIN_DELAY},
+        ui, App,
+    },
+    inputs::key::Key,
+    io::{IoEvent, Settings},
+};
+
+use super::{Screen, ScreenContext, ScreenId, ScreenKey};
+
+/// Sleep delay and API base URL, saved together
+#[derive(Debug, Clone)]
+pub struct SettingsForm {
+    form: Form,
+}
+
+impl SettingsForm {
+    /// Filled with the settings in use
+    pub fn new(app: &App) -> Self {
+        let delay = app
+            .state()
+            .duration()
+            .map_or(MIN_DELAY, |duration| duration.as_secs());
+        let api_url = app.api_url().map(Url::as_str).unwrap_or_default();
+        let form = Form::new(submit)
+            .field(Field::number(
+                "delay",
+                "Sleep delay (s)",
+                delay,
+                MIN_DELAY,
+                MAX_DELAY,
+            ))
+            .field(Field::text("api-url", "API base URL", api_url).validate_with(form::http_url));
+        Self { form }
+    }
+}
+
+fn submit(values: &FormValues) -> IoEvent {
+    IoEvent::UpdateSettings(Settings {
+        delay: values.number("delay").unwrap_or(MIN_DELAY),
+        // Validated already, empty is no API
+        api_url: values.text("api-url").and_then(|url| Url::parse(url).ok()),
+    })
+}
+
+impl Screen for SettingsForm {
+    fn id(&self) -> ScreenId {
+        ScreenId::Settings
+    }
+
+    fn title(&self) -> String {
+        String::from("Settings")
+    }
+
+    fn actions(&self, _app: &App) -> Vec<Action> {
+        vec![]
+    }
+
+    fn form(&self) -> Option<&Form> {
+        Some(&self.form)
+    }
+
+    fn form_mut(&mut self) -> Option<&mut Form> {
+        Some(&mut self.form)
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        self.form.keys()
+    }
+
+    fn on_key(&mut self, key: Key, _state: &AppState) -> ScreenKey {
+        match self.form.on_key(key) {
+            FormKey::Ignored => ScreenKey::Ignored,
+            FormKey::Handled => ScreenKey::Handled,
+            FormKey::Submit(event) => ScreenKey::Dispatch(vec![event]),
+        }
+    }
+
+    fn draw(&mut self, _context: &ScreenContext, area: Rect, buf: &mut Buffer) {
+        ui::draw_form(&self.form, "Settings", area.width).render(area, buf);
+    }
+}
@@ -1,12 +1,24 @@
 use std::time::Duration;
 
-#[derive(Clone)]
+use crate::api::{error::ApiError, models::Item};
+
+/// Bounds of the sleep delay, in seconds
+pub const MIN_DELAY: u64 = 1;
+pub const MAX_DELAY: u64 = 10;
+
+#[derive(Clone, Default)]
 pub enum AppState {
+    #[default]
     Init,
+    /// No usable session, the user has to log in first
+    NeedsLogin { reason: String },
     Initialized {
         duration: Duration,
         counter_sleep: u32,
         counter_tick: u64,
+        items: Vec<Item>,
+        item: Option<Item>,
+        api_error: Option<ApiError>,
     },
 }
 
@@ -19,13 +31,28 @@ impl AppState {
             duration,
             counter_sleep,
             counter_tick,
+            items: Vec::new(),
+            item: None,
+            api_error: None,
         }
     }
 
+    pub fn needs_login(reason: String) -> Self {
+        Self::NeedsLogin { reason }
+    }
+
     pub fn is_initialized(&self) -> bool {
         matches!(self, Self::Initialized { .. })
     }
 
+    pub fn login_reason(&self) -> Option<&str> {
+        if let Self::NeedsLogin { reason } = self {
+            Some(reason)
+        } else {
+            None
+        }
+    }
+
     pub fn incr_sleep(&mut self) {
         if let Self::Initialized { counter_sleep, .. } = self {
             *counter_sleep += 1;
@@ -54,6 +81,56 @@ impl AppState {
         }
     }
 
+    pub fn items(&self) -> &[Item] {
+        if let Self::Initialized { items, .. } = self {
+            items
+        } else {
+            &[]
+        }
+    }
+
+    pub fn item(&self) -> Option<&Item> {
+        if let Self::Initialized { item, .. } = self {
+            item.as_ref()
+        } else {
+            None
+        }
+    }
+
+    pub fn api_error(&self) -> Option<&ApiError> {
+        if let Self::Initialized { api_error, .. } = self {
+            api_error.as_ref()
+        } else {
+            None
+        }
+    }
+
+    pub fn set_items(&mut self, new_items: Vec<Item>) {
+        if let Self::Initialized {
+            items, api_error, ..
+        } = self
+        {
+            *items = new_items;
+            *api_error = None;
+        }
+    }
+
+    pub fn set_item(&mut self, new_item: Item) {
+        if let Self::Initialized {
+            item, api_error, ..
+        } = self
+        {
+            *item = Some(new_item);
+            *api_error = None;
+        }
+    }
+
+    pub fn set_api_error(&mut self, error: ApiError) {
+        if let Self::Initialized { api_error, .. } = self {
+            *api_error = Some(error);
+        }
+    }
+
     pub fn duration(&self) -> Option<&Duration> {
         if let Self::Initialized { duration, .. } = self {
             Some(duration)
@@ -62,23 +139,35 @@ impl AppState {
         }
     }
 
+    pub fn set_delay(&mut self, secs: u64) {
+        if let Self::Initialized { duration, .. } = self {
+            *duration = Duration::from_secs(secs.clamp(MIN_DELAY, MAX_DELAY));
+        }
+    }
+
     pub fn increment_delay(&mut self) {
         if let Self::Initialized { duration, .. } = self {
-            let secs = (duration.as_secs() + 1).clamp(1, 10);
+            let secs = (duration.as_secs() + 1).clamp(MIN_DELAY, MAX_DELAY);
             *duration = Duration::from_secs(secs);
         }
     }
 
     pub fn decrement_delay(&mut self) {
         if let Self::Initialized { duration, .. } = self {
-            let secs = (duration.as_secs() + 1).clamp(1, 10);
+            let secs = duration
+                .as_secs()
+                .saturating_sub(1)
+                .clamp(MIN_DELAY, MAX_DELAY);
             *duration = Duration::from_secs(secs);
         }
     }
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        Self::Init
-    }
+#[derive(Clone, Debug, Default)]
+pub enum AuthStatus {
+    #[default]
+    LoggedOut,
+    LoggingIn,
+    LoggedIn,
+    Failed(String),
 }
@@ -0,0 +1,244 @@
+use std::{cmp::Ordering, collections::BTreeSet};
+
+use crate::inputs::key::{Key, KeyCode, Modifiers};
+
+/// Bounds of a column width, in cells
+pub const MIN_COLUMN_WIDTH: u16 = 3;
+pub const MAX_COLUMN_WIDTH: u16 = 80;
+
+/// What a `TableView` shows, one row per resource
+pub trait TableRow {
+    /// Identifies the row across reloads, so marks stick to it
+    fn key(&self) -> String;
+
+    /// One per column
+    fn cells(&self) -> Vec<String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub title: &'static str,
+    pub width: u16,
+}
+
+/// Sorted by this column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+/// Selection, marks, sorting and column widths of a table
+///
+/// The rows are given to each call rather than kept, so the table follows
+/// the data as it's reloaded. The first row shown is kept by the screen,
+/// `ui::draw_table` scrolls it.
+#[derive(Debug, Clone)]
+pub struct TableView {
+    columns: Vec<Column>,
+    /// Position in the sorted rows, of the first one shown if read-only
+    selected: usize,
+    /// Column the sort and resize keys act on
+    column: usize,
+    sort: Option<Sort>,
+    marked: BTreeSet<String>,
+    /// Only shows the rows, nothing to select or mark
+    read_only: bool,
+}
+
+impl TableView {
+    pub fn new(columns: &[(&'static str, u16)]) -> Self {
+        let columns = columns
+            .iter()
+            .map(|(title, width)| Column {
+                title,
+                width: (*width).clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH),
+            })
+            .collect();
+        Self {
+            columns,
+            selected: 0,
+            column: 0,
+            sort: None,
+            marked: BTreeSet::new(),
+            read_only: false,
+        }
+    }
+
+    /// A list to look at, like the help: its keys do nothing,
+    /// its position is the first row shown
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Index of the current column
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn sort(&self) -> Option<Sort> {
+        self.sort
+    }
+
+    /// Position of the selected row in `order`, of the first row shown if read-only
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn is_marked(&self, row: &impl TableRow) -> bool {
+        self.marked.contains(&row.key())
+    }
+
+    /// Indices of the rows, in the order they're shown
+    pub fn order<R: TableRow>(&self, rows: &[R]) -> Vec<usize> {
+        let mut order = (0..rows.len()).collect::<Vec<_>>();
+        if let Some(sort) = self.sort {
+            let cells = rows.iter().map(TableRow::cells).collect::<Vec<_>>();
+            let cell = |row: usize| cells[row].get(sort.column).map_or("", String::as_str);
+            // Stable, ties keep the order they came in
+            order.sort_by(|a, b| {
+                let ordering = compare_cells(cell(*a), cell(*b));
+                if sort.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+        order
+    }
+
+    /// The selected row, `None` when there are none
+    pub fn selection<'a, R: TableRow>(&self, rows: &'a [R]) -> Option<&'a R> {
+        let order = self.order(rows);
+        let selected = self.selected.min(order.len().checked_sub(1)?);
+        rows.get(order[selected])
+    }
+
+    /// Marked rows in the order they're shown, those gone since are left out
+    pub fn marked<'a, R: TableRow>(&self, rows: &'a [R]) -> Vec<&'a R> {
+        self.order(rows)
+            .into_iter()
+            .map(|row| &rows[row])
+            .filter(|row| self.is_marked(*row))
+            .collect()
+    }
+
+    /// Keys taken by `on_key`, none when read-only
+    pub fn keys(&self) -> Vec<Key> {
+        if self.read_only {
+            return vec![];
+        }
+        let mut keys = "jkhlo <>".chars().map(Key::char).collect::<Vec<_>>();
+        keys.extend(
+            [
+                KeyCode::Down,
+                KeyCode::Up,
+                KeyCode::Left,
+                KeyCode::Right,
+                KeyCode::PageDown,
+                KeyCode::PageUp,
+                KeyCode::Home,
+                KeyCode::End,
+            ]
+            .map(Key::plain),
+        );
+        keys
+    }
+
+    /// Move with j/k, the arrows, PageUp/PageDown (by `page` rows), Home/End,
+    /// mark with Space, pick a column with h/l, sort it with o and resize it
+    /// with `<` and `>`
+    ///
+    /// False if the key is none of those, or the table is read-only.
+    pub fn on_key<R: TableRow>(&mut self, key: Key, rows: &[R], page: usize) -> bool {
+        if self.read_only || (!key.modifiers.is_empty() && key.modifiers != Modifiers::SHIFT) {
+            return false;
+        }
+        let page = page.max(1);
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => self.select(rows.len(), self.selected + 1),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.select(rows.len(), self.selected.saturating_sub(1))
+            }
+            KeyCode::PageDown => self.select(rows.len(), self.selected + page),
+            KeyCode::PageUp => self.select(rows.len(), self.selected.saturating_sub(page)),
+            KeyCode::Home => self.select(rows.len(), 0),
+            KeyCode::End => self.select(rows.len(), usize::MAX),
+            KeyCode::Char(' ') => self.toggle_mark(rows),
+            KeyCode::Left | KeyCode::Char('h') => self.column = self.column.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.column = (self.column + 1).min(self.columns.len().saturating_sub(1))
+            }
+            KeyCode::Char('o') => self.sort_by(self.column, rows),
+            KeyCode::Char('<') => self.resize(self.column, -1),
+            KeyCode::Char('>') => self.resize(self.column, 1),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Select the row at that position, within the rows
+    pub fn select(&mut self, len: usize, selected: usize) {
+        self.selected = selected.min(len.saturating_sub(1));
+    }
+
+    /// Mark the selected row, or unmark it
+    pub fn toggle_mark<R: TableRow>(&mut self, rows: &[R]) {
+        if let Some(key) = self.selection(rows).map(TableRow::key) {
+            if !self.marked.remove(&key) {
+                self.marked.insert(key);
+            }
+        }
+    }
+
+    /// Sort by the column, ascending first then the other way around,
+    /// the selected row stays selected
+    pub fn sort_by<R: TableRow>(&mut self, column: usize, rows: &[R]) {
+        let selected = self.selection(rows).map(TableRow::key);
+        self.sort = Some(match self.sort {
+            Some(sort) if sort.column == column => Sort {
+                column,
+                descending: !sort.descending,
+            },
+            _ => Sort {
+                column,
+                descending: false,
+            },
+        });
+        if let Some(selected) = selected {
+            let order = self.order(rows);
+            self.selected = order
+                .iter()
+                .position(|row| rows[*row].key() == selected)
+                .unwrap_or_default();
+        }
+    }
+
+    /// Widen (or narrow if `delta` is negative) the column, within bounds
+    pub fn resize(&mut self, column: usize, delta: i16) {
+        if let Some(column) = self.columns.get_mut(column) {
+            column.width = column
+                .width
+                .saturating_add_signed(delta)
+                .clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+        }
+    }
+}
+
+/// Numbers by value, the rest as text ignoring case
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
@@ -2,66 +2,200 @@ use std::time::Duration;
 
 use tui::{
     backend::Backend,
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols::line,
     text::{Span, Spans},
-    widgets::{Block, BorderType, Borders, Cell, LineGauge, Paragraph, Row, Table},
+    widgets::{
+        Block, BorderType, Borders, Cell, Clear, LineGauge, List, ListItem, ListState, Paragraph,
+        Row, StatefulWidget, Table, TableState, Widget, Wrap,
+    },
     Frame,
 };
 use tui_logger::TuiLoggerWidget;
 
-use super::{actions::Actions, state::AppState, App};
+use crate::inputs::key::{Key, KeySequence};
 
-pub fn draw<B>(rect: &mut Frame<B>, app: &App)
+use super::{
+    actions::{Action, Actions},
+    command::{self, CommandLine},
+    form::{FieldKind, Form},
+    input::TextInput,
+    jobs::{JobStatus, Jobs},
+    modal::{Modal, ModalKind},
+    palette::Palette,
+    screens::ScreenWidget,
+    table::{TableRow, TableView},
+    App,
+};
+
+/// Below this width the help pane is hidden
+pub const FULL_WIDTH: u16 = 80;
+/// Below this height the logs pane is hidden
+pub const FULL_HEIGHT: u16 = 24;
+/// Below this size nothing useful fits
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 10;
+
+/// Where each pane goes for a given terminal size
+pub struct Areas {
+    pub title: Rect,
+    pub body: Rect,
+    pub help: Option<Rect>,
+    pub duration: Rect,
+    pub logs: Option<Rect>,
+}
+
+/// Collapse the help and logs panes on small terminals,
+/// `None` when the terminal is too small for anything
+pub fn areas(size: Rect) -> Option<Areas> {
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        return None;
+    }
+    let show_help = size.width >= FULL_WIDTH;
+    let show_logs = size.height >= FULL_HEIGHT;
+
+    let mut constraints = vec![
+        Constraint::Length(3),
+        Constraint::Min(4),
+        Constraint::Length(3),
+    ];
+    if show_logs {
+        constraints.push(Constraint::Length(12));
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(size);
+
+    let (body, help) = if show_help {
+        let body_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(20), Constraint::Length(32)].as_ref())
+            .split(chunks[1]);
+        (body_chunks[0], Some(body_chunks[1]))
+    } else {
+        (chunks[1], None)
+    };
+
+    Some(Areas {
+        title: chunks[0],
+        body,
+        help,
+        duration: chunks[2],
+        logs: chunks.get(3).copied(),
+    })
+}
+
+pub fn draw<B>(rect: &mut Frame<B>, app: &mut App)
 where
     B: Backend,
 {
     let size = rect.size();
-    check_size(&size);
+    let areas = match areas(size) {
+        Some(areas) => areas,
+        None => {
+            // Vertically centered, when there's room for it
+            let offset = size.height.saturating_sub(2) / 2;
+            let area = Rect::new(size.x, size.y + offset, size.width, size.height - offset);
+            rect.render_widget(draw_too_small(size), area);
+            return;
+        }
+    };
 
-    let chunks = Layout::default()
+    // Title block
+    let title = draw_title(app);
+    rect.render_widget(title, areas.title);
+
+    let jobs_count = app.jobs().in_flight().count() as u16;
+    let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(3),
-                Constraint::Min(10),
-                Constraint::Length(3),
-                Constraint::Length(12),
+                Constraint::Min(6),
+                Constraint::Length(jobs_count.min(5) + 2),
             ]
             .as_ref(),
         )
-        .split(size);
-
-    // Title block
-    let title = draw_title();
-    rect.render_widget(title, chunks[0]);
-
-    let body_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Min(20), Constraint::Length(32)].as_ref())
-        .split(chunks[1]);
+        .split(areas.body);
 
-    let body = draw_body(app.is_loading(), app.state());
-    rect.render_widget(body, body_chunks[0]);
+    let screen = ScreenWidget { app: &mut *app };
+    if jobs_count > 0 {
+        rect.render_widget(screen, main_chunks[0]);
+        let jobs = draw_jobs(app.jobs());
+        rect.render_widget(jobs, main_chunks[1]);
+    } else {
+        rect.render_widget(screen, areas.body);
+    }
 
-    let help = draw_help(app.actions());
-    rect.render_widget(help, body_chunks[1]);
+    if let Some(area) = areas.help {
+        let help = draw_help(app.help(), app.actions(), app.count());
+        rect.render_widget(help, area);
+    }
 
     // Duration LineGauge
     if let Some(duration) = app.state().duration() {
         let duration_block = draw_duration(duration);
-        rect.render_widget(duration_block, chunks[2]);
+        rect.render_widget(duration_block, areas.duration);
+    }
+
+    // Command line, in place of the duration
+    if app.command_line().is_open() {
+        rect.render_widget(Clear, areas.duration);
+        rect.render_widget(
+            draw_command_line(app.command_line(), areas.duration.width),
+            areas.duration,
+        );
     }
 
     // Logs
-    let logs = draw_logs();
-    rect.render_widget(logs, chunks[3]);
+    if let Some(area) = areas.logs {
+        let logs = draw_logs();
+        rect.render_widget(logs, area);
+    }
+
+    // Startup problems, over the rest
+    if !app.diagnostics().is_empty() {
+        rect.render_widget(Clear, areas.body);
+        rect.render_widget(draw_diagnostics(app.diagnostics()), areas.body);
+        return;
+    }
+
+    // Command palette, over the rest
+    if let Some(palette) = app.palette() {
+        draw_palette(rect, size, palette, app.actions());
+    }
+
+    // Modal, over the palette too
+    if let Some(modal) = app.modal() {
+        draw_modal(rect, size, modal);
+        return;
+    }
+
+    // Which key, over the rest
+    if !app.pending_keys().is_empty() {
+        let continuations = app.actions().continuations(app.pending_keys());
+        let area = which_key_area(areas.body, continuations.len() as u16);
+        rect.render_widget(Clear, area);
+        let which_key = draw_which_key(app.count(), app.pending_keys(), continuations);
+        rect.render_widget(which_key, area);
+    }
 }
 
-fn draw_title<'a>() -> Paragraph<'a> {
-    Paragraph::new("Churro CLI")
-        .style(Style::default().fg(Color::LightCyan))
+/// App name and the path to the shown screen
+fn draw_title<'a>(app: &App) -> Paragraph<'a> {
+    let color = if app.is_focused() {
+        Color::LightCyan
+    } else {
+        Color::DarkGray
+    };
+    let title = std::iter::once(String::from("Churro CLI"))
+        .chain(app.screens().map(|screen| screen.title()))
+        .collect::<Vec<_>>()
+        .join(" › ");
+    Paragraph::new(title)
+        .style(Style::default().fg(color))
         .alignment(Alignment::Center)
         .block(
             Block::default()
@@ -71,57 +205,45 @@ fn draw_title<'a>() -> Paragraph<'a> {
         )
 }
 
-fn check_size(rect: &Rect) {
-    if rect.width < 80 {
-        panic!(
-            "Terminal width needs to be at least 80, (got {})",
-            rect.width
-        );
-    }
-    if rect.height < 24 {
-        panic!(
-            "Terminal height needs to be at least 24, (got {})",
-            rect.height
-        );
-    }
+fn draw_too_small<'a>(size: Rect) -> Paragraph<'a> {
+    Paragraph::new(vec![
+        Spans::from(Span::raw("Terminal too small")),
+        Spans::from(Span::raw(format!(
+            "need {}x{}, got {}x{}",
+            MIN_WIDTH, MIN_HEIGHT, size.width, size.height
+        ))),
+    ])
+    .style(Style::default().fg(Color::Yellow))
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true })
 }
 
-fn draw_body<'a>(loading: bool, state: &AppState) -> Paragraph<'a> {
-    let initialized_text = if state.is_initialized() {
-        "Initialized"
-    } else {
-        "Not Initialized !"
-    };
-    let loading_text = if loading { "Loading..." } else { "" };
-    let sleep_text = if let Some(sleeps) = state.count_sleep() {
-        format!("Sleep count: {}", sleeps)
-    } else {
-        String::default()
-    };
-    let tick_text = if let Some(ticks) = state.count_tick() {
-        format!("Tick count: {}", ticks)
-    } else {
-        String::default()
-    };
+fn draw_jobs(jobs: &Jobs) -> List<'_> {
+    let items = jobs
+        .in_flight()
+        .map(|job| {
+            let status_style = match job.status() {
+                JobStatus::Running => Style::default().fg(Color::LightCyan),
+                _ => Style::default().fg(Color::Gray),
+            };
+            ListItem::new(Spans::from(vec![
+                Span::raw(format!("{:>4} ", job.id().to_string())),
+                Span::raw(format!("{:<20} ", job.event().to_string())),
+                Span::styled(format!("{:<8} ", job.status().to_string()), status_style),
+                Span::raw(format!("{:.1}s", job.elapsed().as_secs_f64())),
+            ]))
+        })
+        .collect::<Vec<_>>();
 
-    Paragraph::new(vec![
-        Spans::from(Span::raw(initialized_text)),
-        Spans::from(Span::raw(loading_text)),
-        Spans::from(Span::raw(sleep_text)),
-        Spans::from(Span::raw(tick_text)),
-    ])
-    .style(Style::default().fg(Color::LightCyan))
-    .alignment(Alignment::Left)
-    .block(
+    List::new(items).block(
         Block::default()
-            // .title("Body")
             .borders(Borders::ALL)
-            .style(Style::default().fg(Color::White))
-            .border_type(BorderType::Plain),
+            .border_type(BorderType::Plain)
+            .title("Jobs"),
     )
 }
 
-fn draw_duration(duration: &Duration) -> LineGauge {
+fn draw_duration(duration: &Duration) -> LineGauge<'_> {
     let sec = duration.as_secs();
     let label = format!("{}s", sec);
     let ratio = sec as f64 / 10.0;
@@ -142,37 +264,553 @@ fn draw_duration(duration: &Duration) -> LineGauge {
         .ratio(ratio)
 }
 
-fn draw_help(actions: &Actions) -> Table {
-    let key_style = Style::default().fg(Color::LightCyan);
-    let help_style = Style::default().fg(Color::Gray);
+/// A key of an action in the help, the action is only named on its first key
+struct HelpRow {
+    keys: &'static KeySequence,
+    action: Action,
+    first: bool,
+}
 
-    let mut rows = vec![];
-    for action in actions.actions().iter() {
-        let mut first = true;
-        for key in action.keys() {
-            let help = if first {
-                first = false;
-                action.to_string()
+impl TableRow for HelpRow {
+    fn key(&self) -> String {
+        self.keys.to_string()
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let action = if self.first {
+            self.action.to_string()
+        } else {
+            String::new()
+        };
+        vec![self.keys.to_string(), action]
+    }
+}
+
+fn help_rows(actions: &Actions) -> Vec<HelpRow> {
+    actions
+        .actions()
+        .iter()
+        .flat_map(|action| {
+            action
+                .keys()
+                .iter()
+                .enumerate()
+                .map(move |(i, keys)| HelpRow {
+                    keys,
+                    action: *action,
+                    first: i == 0,
+                })
+        })
+        .collect()
+}
+
+/// The help pane, read-only so its position is how far it's scrolled
+pub fn help_table() -> TableView {
+    TableView::new(&[("Keys", 12), ("Action", 17)]).read_only()
+}
+
+/// Scroll the help by `delta` rows, no further than its last row
+pub fn scroll_help(help: &mut TableView, actions: &Actions, area: Rect, delta: isize) {
+    // Borders and header
+    let shown = area.height.saturating_sub(3) as usize;
+    let last = help_rows(actions).len().saturating_sub(shown);
+    let top = help.selected().min(last).saturating_add_signed(delta);
+    help.select(last + 1, top);
+}
+
+/// The action whose help row is at the given terminal position
+pub fn help_action_at(
+    help: &TableView,
+    actions: &Actions,
+    area: Rect,
+    column: u16,
+    row: u16,
+) -> Option<Action> {
+    // Skip the borders and the header
+    let inside = column > area.x
+        && column < area.right().saturating_sub(1)
+        && row > area.y + 1
+        && row < area.bottom().saturating_sub(1);
+    if !inside {
+        return None;
+    }
+    let rows = help_rows(actions);
+    let shown = area.height.saturating_sub(3) as usize;
+    let top = help.selected().min(rows.len().saturating_sub(shown));
+    let index = top + (row - area.y - 2) as usize;
+    rows.get(index).map(|row| row.action)
+}
+
+/// Help pane, drawn as a read-only table
+struct Help<'a> {
+    table: &'a TableView,
+    actions: &'a Actions,
+    count: Option<u32>,
+}
+
+impl Widget for Help<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let title = match self.count {
+            Some(count) => format!("Help ({})", count),
+            None => String::from("Help"),
+        };
+        // Nothing selected, the table scrolls by its own position
+        draw_table(
+            self.table,
+            &mut 0,
+            &help_rows(self.actions),
+            title,
+            area,
+            buf,
+        );
+    }
+}
+
+/// The count typed so far goes in the title
+fn draw_help<'a>(table: &'a TableView, actions: &'a Actions, count: Option<u32>) -> Help<'a> {
+    Help {
+        table,
+        actions,
+        count,
+    }
+}
+
+/// Query line over the matching actions, with their keys,
+/// the ones not available right now are dimmed
+fn draw_palette<B>(rect: &mut Frame<B>, size: Rect, palette: &Palette, actions: &Actions)
+where
+    B: Backend,
+{
+    let matches = palette.matches();
+    let width = size.width.saturating_sub(4).min(60);
+    let height = (matches.len() as u16 + 4).min(size.height.saturating_sub(2));
+    let area = Rect::new(
+        size.x + (size.width - width) / 2,
+        size.y + (size.height - height) / 3,
+        width,
+        height,
+    );
+    rect.render_widget(Clear, area);
+
+    let mut title = String::from("Commands");
+    if let Some(count) = palette.count() {
+        title = format!("{} ({})", title, count);
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .title(title);
+    let inner = block.inner(area);
+    rect.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let mut query = vec![Span::styled("> ", Style::default().fg(Color::LightCyan))];
+    query.extend(input_spans(palette.field(), inner.width.saturating_sub(2)));
+    let query = Paragraph::new(Spans::from(query)).block(Block::default().borders(Borders::BOTTOM));
+    rect.render_widget(query, chunks[0]);
+
+    let items = matches
+        .iter()
+        .map(|action| {
+            let available = actions.actions().contains(action);
+            let (name_style, key_style) = if available {
+                (
+                    Style::default().fg(Color::White),
+                    Style::default().fg(Color::LightCyan),
+                )
+            } else {
+                let dim = Style::default().fg(Color::DarkGray);
+                (dim, dim)
+            };
+            let keys = action
+                .keys()
+                .iter()
+                .map(KeySequence::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            ListItem::new(Spans::from(vec![
+                Span::styled(format!("{:<24}", action.to_string()), name_style),
+                Span::styled(keys, key_style),
+            ]))
+        })
+        .collect::<Vec<_>>();
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default();
+    if !matches.is_empty() {
+        state.select(Some(palette.selected()));
+    }
+    rect.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+/// Centered box with the message, then the buttons, options or input
+fn draw_modal<B>(rect: &mut Frame<B>, size: Rect, modal: &Modal)
+where
+    B: Backend,
+{
+    let width = size.width.saturating_sub(4).min(50);
+    let highlight = Style::default()
+        .bg(Color::DarkGray)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = modal
+        .message()
+        .lines()
+        .map(|line| Spans::from(Span::raw(line)))
+        .collect::<Vec<_>>();
+    lines.push(Spans::default());
+    match modal.kind() {
+        ModalKind::Confirm { yes } => {
+            let (yes_style, no_style) = if *yes {
+                (highlight, Style::default())
+            } else {
+                (Style::default(), highlight)
+            };
+            lines.push(Spans::from(vec![
+                Span::styled(" Yes ", yes_style),
+                Span::raw("   "),
+                Span::styled(" No ", no_style),
+            ]));
+        }
+        ModalKind::Choice { options, selected } => {
+            lines.extend(options.iter().enumerate().map(|(i, option)| {
+                let style = if i == *selected {
+                    highlight
+                } else {
+                    Style::default()
+                };
+                let number = if i < 9 {
+                    format!("{} ", i + 1)
+                } else {
+                    "  ".to_string()
+                };
+                Spans::from(vec![
+                    Span::styled(number, Style::default().fg(Color::LightCyan)),
+                    Span::styled(option.as_str(), style),
+                ])
+            }));
+        }
+        ModalKind::Text { input } => {
+            let mut spans = vec![Span::styled("> ", Style::default().fg(Color::LightCyan))];
+            // Inside the borders, after the prompt
+            spans.extend(input_spans(input, width.saturating_sub(4)));
+            lines.push(Spans::from(spans));
+        }
+    }
+
+    let height = (lines.len() as u16 + 2).min(size.height);
+    let area = Rect::new(
+        size.x + (size.width - width) / 2,
+        size.y + (size.height - height) / 3,
+        width,
+        height,
+    );
+    rect.render_widget(Clear, area);
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain)
+            .style(Style::default().fg(Color::White))
+            .title(modal.title()),
+    );
+    rect.render_widget(paragraph, area);
+}
+
+/// The line being typed, then its error, the completions or the command usage
+fn draw_command_line(command_line: &CommandLine, width: u16) -> Paragraph<'_> {
+    let hint_style = Style::default().fg(Color::DarkGray);
+    let mut spans = vec![Span::styled(":", Style::default().fg(Color::LightCyan))];
+    // Inside the borders, after the colon
+    spans.extend(input_spans(command_line.field(), width.saturating_sub(3)));
+    spans.push(Span::raw("  "));
+    if let Some(error) = command_line.error() {
+        spans.push(Span::styled(error, Style::default().fg(Color::Red)));
+    } else if let Some((candidates, index)) = command_line.completions() {
+        for (i, candidate) in candidates.iter().enumerate() {
+            let style = if i == index {
+                Style::default().fg(Color::LightCyan)
             } else {
-                String::from("")
+                hint_style
             };
-            let row = Row::new(vec![
-                Cell::from(Span::styled(key.to_string(), key_style)),
-                Cell::from(Span::styled(help, help_style)),
-            ]);
-            rows.push(row);
+            let word = candidate.split_whitespace().last().unwrap_or_default();
+            spans.push(Span::styled(format!("{} ", word), style));
+        }
+    } else if let Some(name) = command_line.input().split_whitespace().next() {
+        spans.push(Span::styled(command::usage(name), hint_style));
+    }
+
+    Paragraph::new(Spans::from(spans)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain)
+            .title("Command"),
+    )
+}
+
+/// The visible part of the input, the cursor in reverse video
+fn input_spans(input: &TextInput, width: u16) -> Vec<Span<'static>> {
+    let view = input.view(width as usize);
+    vec![
+        Span::raw(view.before),
+        Span::styled(
+            view.cursor,
+            Style::default().add_modifier(Modifier::REVERSED),
+        ),
+        Span::raw(view.after),
+    ]
+}
+
+/// One line per field, its error under it, the focused one editable
+pub fn draw_form<'a>(form: &'a Form, title: &'a str, width: u16) -> Paragraph<'a> {
+    const LABEL_WIDTH: usize = 18;
+    let label_style = Style::default().fg(Color::Gray);
+    let focus_style = Style::default().fg(Color::LightCyan);
+    let mut lines = vec![];
+    for (i, field) in form.fields().iter().enumerate() {
+        let focused = i == form.focused();
+        let mut spans = vec![
+            Span::styled(if focused { "› " } else { "  " }, focus_style),
+            Span::styled(
+                format!("{:<width$}", field.label(), width = LABEL_WIDTH),
+                if focused { focus_style } else { label_style },
+            ),
+        ];
+        match field.kind() {
+            FieldKind::Text(input) | FieldKind::Number { input, .. } => {
+                // Inside the borders, after the label
+                let input_width = width.saturating_sub(LABEL_WIDTH as u16 + 4);
+                if focused {
+                    spans.extend(input_spans(input, input_width));
+                } else {
+                    let view = input.view(usize::MAX);
+                    spans.push(Span::raw(view.before + &view.cursor + &view.after));
+                }
+            }
+            FieldKind::Toggle(on) => {
+                spans.push(Span::raw(if *on { "[x]" } else { "[ ]" }));
+            }
+            FieldKind::Select { options, selected } => {
+                let option = options.get(*selected).map(String::as_str);
+                spans.push(Span::raw(format!("‹ {} ›", option.unwrap_or_default())));
+            }
+        }
+        lines.push(Spans::from(spans));
+        if let Some(error) = field.error() {
+            lines.push(Spans::from(Span::styled(
+                format!("  {:<width$}{}", "", error, width = LABEL_WIDTH),
+                Style::default().fg(Color::Red),
+            )));
         }
     }
+    lines.push(Spans::default());
+    let back = Action::Back
+        .keys()
+        .first()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    lines.push(Spans::from(Span::styled(
+        format!("<Tab> next field  <Enter> save  {} back", back),
+        Style::default().fg(Color::DarkGray),
+    )));
 
-    Table::new(rows)
+    Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::White))
+            .border_type(BorderType::Plain)
+            .title(title),
+    )
+}
+
+/// Rows in the table's order under a header showing the current column
+/// and the sort, marked rows starred, with a scrollbar on the right border
+/// when they don't all fit
+///
+/// `top` keeps the first row shown between draws, scrolled as little as
+/// the selection needs. Returns how many rows fit, what PageUp and PageDown
+/// move by.
+pub fn draw_table<R: TableRow>(
+    table: &TableView,
+    top: &mut usize,
+    rows: &[R],
+    title: String,
+    area: Rect,
+    buf: &mut Buffer,
+) -> usize {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White))
+        .border_type(BorderType::Plain)
+        .title(title);
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    // Read-only tables have nothing to mark
+    let marks = !table.is_read_only();
+    let header_style = Style::default().fg(Color::Gray);
+    let header = Row::new(marks.then(|| Cell::from("")).into_iter().chain(
+        table.columns().iter().enumerate().map(|(i, column)| {
+            let arrow = match table.sort() {
+                Some(sort) if sort.column == i && sort.descending => " ▼",
+                Some(sort) if sort.column == i => " ▲",
+                _ => "",
+            };
+            let style = if i == table.column() && marks {
+                header_style
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::UNDERLINED)
+            } else {
+                header_style
+            };
+            Cell::from(Span::styled(format!("{}{}", column.title, arrow), style))
+        }),
+    ));
+
+    // The header takes the first line
+    let height = inner.height.saturating_sub(1) as usize;
+    let order = table.order(rows);
+    let selected = match order.len() {
+        0 => None,
+        _ if table.is_read_only() => None,
+        len => Some(table.selected().min(len - 1)),
+    };
+    // Read-only tables scroll by their position, others follow the selection
+    *top = match selected {
+        Some(selected) => (*top)
+            .min(order.len().saturating_sub(height))
+            .min(selected)
+            .max((selected + 1).saturating_sub(height)),
+        None => table.selected().min(order.len().saturating_sub(height)),
+    };
+    let top = *top;
+    let body = order.iter().skip(top).map(|row| {
+        let row = &rows[*row];
+        let mark = if table.is_marked(row) { "*" } else { " " };
+        let mark = Cell::from(Span::styled(mark, Style::default().fg(Color::Yellow)));
+        Row::new(
+            marks
+                .then_some(mark)
+                .into_iter()
+                .chain(row.cells().into_iter().map(Cell::from)),
+        )
+    });
+    let widths = marks
+        .then_some(Constraint::Length(1))
+        .into_iter()
+        .chain(
+            table
+                .columns()
+                .iter()
+                .map(|column| Constraint::Length(column.width)),
+        )
+        .collect::<Vec<_>>();
+
+    // The rows start at the top already, the state only highlights
+    let mut state = TableState::default();
+    state.select(selected.map(|selected| selected - top));
+    let widget = Table::new(body)
+        .header(header)
+        .widths(&widths)
+        .column_spacing(1)
+        .style(Style::default().fg(Color::LightCyan))
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    StatefulWidget::render(widget, inner, buf, &mut state);
+
+    // Where the selection is in the rows
+    if order.len() > height && height > 0 && area.width > 0 {
+        let thumb = (height * height / order.len()).max(1);
+        let thumb_top = match selected {
+            Some(selected) => selected * (height - thumb) / (order.len() - 1),
+            None => top * (height - thumb) / (order.len() - height),
+        };
+        let x = area.right() - 1;
+        for y in thumb_top..thumb_top + thumb {
+            buf.get_mut(x, inner.y + 1 + y as u16)
+                .set_symbol("█")
+                .set_style(Style::default().fg(Color::LightCyan));
+        }
+    }
+    height
+}
+
+fn draw_diagnostics(diagnostics: &[String]) -> Paragraph<'_> {
+    let mut lines = diagnostics
+        .iter()
+        .flat_map(|diagnostic| diagnostic.lines().chain([""]))
+        .map(|line| Spans::from(Span::raw(line)))
+        .collect::<Vec<_>>();
+    lines.push(Spans::from(Span::styled(
+        "Press Enter to continue, q to quit",
+        Style::default().fg(Color::LightCyan),
+    )));
+
+    Paragraph::new(lines)
+        .style(Style::default().fg(Color::Yellow))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false })
         .block(
             Block::default()
                 .borders(Borders::ALL)
+                .style(Style::default().fg(Color::White))
                 .border_type(BorderType::Plain)
-                .title("Help"),
+                .title("Configuration problems"),
         )
-        .widths(&[Constraint::Length(11), Constraint::Min(20)])
-        .column_spacing(1)
+}
+
+/// Bottom right corner of the body, sized for the continuations
+fn which_key_area(body: Rect, count: u16) -> Rect {
+    let width = body.width.min(36);
+    let height = (count + 2).min(body.height);
+    Rect::new(body.right() - width, body.bottom() - height, width, height)
+}
+
+/// What the pending keys can be followed by
+fn draw_which_key<'a>(
+    count: Option<u32>,
+    pending: &[Key],
+    continuations: Vec<(&KeySequence, Action)>,
+) -> List<'a> {
+    let key_style = Style::default().fg(Color::LightCyan);
+    let help_style = Style::default().fg(Color::Gray);
+
+    let items = continuations
+        .into_iter()
+        .map(|(keys, action)| {
+            let rest = keys.keys()[pending.len()..]
+                .iter()
+                .map(Key::to_string)
+                .collect::<Vec<_>>()
+                .join(" ");
+            ListItem::new(Spans::from(vec![
+                Span::styled(format!("{:<12} ", rest), key_style),
+                Span::styled(action.to_string(), help_style),
+            ]))
+        })
+        .collect::<Vec<_>>();
+
+    let mut title = pending
+        .iter()
+        .map(Key::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Some(count) = count {
+        title = format!("{} {}", count, title);
+    }
+    List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain)
+            .title(format!("{} -", title)),
+    )
 }
 
 fn draw_logs<'a>() -> TuiLoggerWidget<'a> {
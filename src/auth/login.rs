@@ -0,0 +1,187 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use log::{debug, info};
+use oauth2::{
+    reqwest::async_http_client, AuthorizationCode, CsrfToken, PkceCodeChallenge, RedirectUrl,
+};
+use tokio::sync::oneshot;
+use url::Url;
+
+use super::{OAuthConfig, Tokens};
+
+const CALLBACK_PATH: &str = "/callback";
+
+/// Run the authorization code + PKCE flow
+///
+/// `open` receives the provider authorize url, in the CLI this is the web
+/// browser, but anything able to follow the redirects will do.
+pub async fn login<F>(config: &OAuthConfig, open: F) -> Result<Tokens>
+where
+    F: FnOnce(&Url) -> Result<()>,
+{
+    let callback = CallbackServer::bind(config.redirect_port)?;
+    let redirect_url = format!("http://{}{}", callback.addr(), CALLBACK_PATH);
+    let client = config
+        .client()?
+        .set_redirect_uri(RedirectUrl::new(redirect_url).context("Invalid redirect url")?);
+
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    let (auth_url, csrf_token) = client
+        .authorize_url(CsrfToken::new_random)
+        .add_scopes(config.scopes())
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+    debug!(
+        "Waiting for the authorization callback on {}",
+        callback.addr()
+    );
+    open(&auth_url)?;
+
+    let params = tokio::time::timeout(config.callback_timeout, callback.wait())
+        .await
+        .context("Timed out waiting for the authorization callback")??;
+
+    if params.state != *csrf_token.secret() {
+        bail!("Authorization callback state does not match, aborting login");
+    }
+
+    info!("🔑 Authorization code received, requesting tokens");
+    let response = client
+        .exchange_code(AuthorizationCode::new(params.code))
+        .set_pkce_verifier(pkce_verifier)
+        .request_async(async_http_client)
+        .await
+        .map_err(|err| anyhow!("Token exchange failed: {}", err))?;
+
+    Ok(Tokens::from_response(&response))
+}
+
+/// Open the authorize url in the user web browser
+pub fn open_browser(url: &Url) -> Result<()> {
+    webbrowser::open(url.as_str()).context("Could not open the web browser")
+}
+
+/// Parameters the provider sends back on the redirect
+struct CallbackParams {
+    code: String,
+    state: String,
+}
+
+type CallbackSender = Arc<Mutex<Option<oneshot::Sender<Result<CallbackParams>>>>>;
+
+/// Short-lived local server receiving the provider redirect
+///
+/// Stops when dropped.
+struct CallbackServer {
+    addr: SocketAddr,
+    rx: oneshot::Receiver<Result<CallbackParams>>,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl CallbackServer {
+    fn bind(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .with_context(|| format!("Could not listen on 127.0.0.1:{}", port))?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let (tx, rx) = oneshot::channel();
+        let tx: CallbackSender = Arc::new(Mutex::new(Some(tx)));
+        let make_svc = make_service_fn(move |_| {
+            let tx = tx.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |req| handle_callback(req, tx.clone()))) }
+        });
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = Server::from_tcp(listener)?
+            .serve(make_svc)
+            .with_graceful_shutdown(async {
+                shutdown_rx.await.ok();
+            });
+        tokio::spawn(server);
+
+        Ok(Self {
+            addr,
+            rx,
+            _shutdown: shutdown_tx,
+        })
+    }
+
+    fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    async fn wait(self) -> Result<CallbackParams> {
+        self.rx
+            .await
+            .context("Callback server stopped before receiving the redirect")?
+    }
+}
+
+async fn handle_callback(
+    req: Request<Body>,
+    tx: CallbackSender,
+) -> Result<Response<Body>, Infallible> {
+    if req.uri().path() != CALLBACK_PATH {
+        // e.g. favicon requests, keep waiting for the real redirect
+        let response = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap_or_default();
+        return Ok(response);
+    }
+
+    let query: HashMap<String, String> =
+        url::form_urlencoded::parse(req.uri().query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+    let params = match (query.get("code"), query.get("state"), query.get("error")) {
+        (_, _, Some(error)) => Err(anyhow!(
+            "Authorization denied: {} {}",
+            error,
+            query
+                .get("error_description")
+                .map(String::as_str)
+                .unwrap_or_default()
+        )),
+        (Some(code), Some(state), None) => Ok(CallbackParams {
+            code: code.clone(),
+            state: state.clone(),
+        }),
+        _ => Err(anyhow!("Authorization callback is missing code or state")),
+    };
+
+    let (status, message) = match params {
+        Ok(_) => (
+            StatusCode::OK,
+            "Churro CLI: logged in, you can close this window.",
+        ),
+        Err(_) => (
+            StatusCode::BAD_REQUEST,
+            "Churro CLI: login failed, see the application logs.",
+        ),
+    };
+
+    if let Some(tx) = tx.lock().ok().and_then(|mut tx| tx.take()) {
+        // The receiver is gone if the login was abandoned, nothing to do then
+        let _ = tx.send(params);
+    }
+
+    let response = Response::builder()
+        .status(status)
+        .header("content-type", "text/plain; charset=utf-8")
+        .body(Body::from(message))
+        .unwrap_or_default();
+    Ok(response)
+}
@@ -0,0 +1,110 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Context, Result};
+use oauth2::{
+    basic::{BasicClient, BasicTokenResponse},
+    AuthUrl, ClientId, ClientSecret, Scope, TokenResponse, TokenUrl,
+};
+use serde::{Deserialize, Serialize};
+
+pub mod login;
+pub mod session;
+pub mod store;
+
+/// Port the local callback server listens on when none is configured
+pub const DEFAULT_REDIRECT_PORT: u16 = 8912;
+
+/// OAuth2 provider settings, usually read from the environment (or `.env`)
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub auth_url: String,
+    pub token_url: String,
+    pub scopes: Vec<String>,
+    /// Port of the local redirect server, `0` picks a free one
+    pub redirect_port: u16,
+    /// How long we wait for the browser to come back to us
+    pub callback_timeout: Duration,
+}
+
+impl OAuthConfig {
+    /// Read the provider settings from `OAUTH_*` environment variables
+    ///
+    /// `OAUTH_CLIENT_ID`, `OAUTH_AUTH_URL` and `OAUTH_TOKEN_URL` are required,
+    /// `OAUTH_CLIENT_SECRET`, `OAUTH_SCOPES` (space separated) and
+    /// `OAUTH_REDIRECT_PORT` are optional.
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).with_context(|| format!("{} is not set", name));
+
+        let redirect_port = match std::env::var("OAUTH_REDIRECT_PORT") {
+            Ok(port) => port
+                .parse()
+                .with_context(|| format!("Invalid OAUTH_REDIRECT_PORT: {}", port))?,
+            Err(_) => DEFAULT_REDIRECT_PORT,
+        };
+        let scopes = std::env::var("OAUTH_SCOPES")
+            .map(|scopes| scopes.split_whitespace().map(String::from).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            client_id: var("OAUTH_CLIENT_ID")?,
+            client_secret: std::env::var("OAUTH_CLIENT_SECRET").ok(),
+            auth_url: var("OAUTH_AUTH_URL")?,
+            token_url: var("OAUTH_TOKEN_URL")?,
+            scopes,
+            redirect_port,
+            callback_timeout: Duration::from_secs(300),
+        })
+    }
+
+    pub(crate) fn client(&self) -> Result<BasicClient> {
+        let auth_url = AuthUrl::new(self.auth_url.clone())
+            .map_err(|err| anyhow!("Invalid auth url {}: {}", self.auth_url, err))?;
+        let token_url = TokenUrl::new(self.token_url.clone())
+            .map_err(|err| anyhow!("Invalid token url {}: {}", self.token_url, err))?;
+
+        Ok(BasicClient::new(
+            ClientId::new(self.client_id.clone()),
+            self.client_secret.clone().map(ClientSecret::new),
+            auth_url,
+            Some(token_url),
+        ))
+    }
+
+    pub(crate) fn scopes(&self) -> impl Iterator<Item = Scope> + '_ {
+        self.scopes.iter().cloned().map(Scope::new)
+    }
+}
+
+/// Tokens obtained from the provider
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Tokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    /// When the access token stops being valid, if the provider told us
+    pub expires_at: Option<SystemTime>,
+}
+
+impl Tokens {
+    pub fn from_response(response: &BasicTokenResponse) -> Self {
+        Self {
+            access_token: response.access_token().secret().clone(),
+            refresh_token: response.refresh_token().map(|token| token.secret().clone()),
+            expires_at: response
+                .expires_in()
+                .map(|expires_in| SystemTime::now() + expires_in),
+        }
+    }
+}
+
+impl std::fmt::Debug for Tokens {
+    // Never leak secrets into the logs
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tokens")
+            .field("access_token", &"***")
+            .field("refresh_token", &self.refresh_token.as_ref().map(|_| "***"))
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
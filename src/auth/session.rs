@@ -0,0 +1,134 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, bail, Result};
+use log::{info, warn};
+use oauth2::{
+    basic::BasicErrorResponseType, reqwest::async_http_client, RefreshToken, RequestTokenError,
+};
+
+use super::{store::TokenStore, OAuthConfig, Tokens};
+
+/// Refresh the access token when it expires in less than this
+pub const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Current user tokens, kept in sync with the token store
+pub struct Session {
+    config: OAuthConfig,
+    store: TokenStore,
+    tokens: Option<Tokens>,
+}
+
+impl Session {
+    pub fn new(config: OAuthConfig, store: TokenStore) -> Self {
+        Self {
+            config,
+            store,
+            tokens: None,
+        }
+    }
+
+    pub fn config(&self) -> &OAuthConfig {
+        &self.config
+    }
+
+    pub fn is_logged_in(&self) -> bool {
+        self.tokens.is_some()
+    }
+
+    /// Load the stored tokens, refreshing them if needed
+    pub async fn restore(&mut self) -> Result<()> {
+        match self.store.load()? {
+            Some(tokens) => self.tokens = Some(tokens),
+            None => bail!("Not logged in"),
+        }
+        self.access_token().await.map(|_| ())
+    }
+
+    /// Keep and persist freshly obtained tokens
+    pub fn set_tokens(&mut self, tokens: Tokens) -> Result<()> {
+        let saved = self.store.save(&tokens);
+        self.tokens = Some(tokens);
+        saved
+    }
+
+    /// Forget the tokens, on disk too
+    pub fn logout(&mut self) -> Result<()> {
+        self.tokens = None;
+        self.store.clear()
+    }
+
+    /// A valid access token, refreshed first if close to expiry
+    pub async fn access_token(&mut self) -> Result<String> {
+        let tokens = self
+            .tokens
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not logged in"))?;
+        if !expires_soon(tokens) {
+            return Ok(tokens.access_token.clone());
+        }
+
+        match self.refresh().await {
+            Ok(tokens) => {
+                let access_token = tokens.access_token.clone();
+                if let Err(err) = self.set_tokens(tokens) {
+                    warn!("Could not save the refreshed tokens: {}", err);
+                }
+                Ok(access_token)
+            }
+            Err(RefreshError::Rejected(err)) => {
+                // The stored tokens are useless now
+                self.logout()?;
+                Err(err)
+            }
+            // Offline or the provider is down, the next call tries again
+            Err(RefreshError::Failed(err)) => Err(err),
+        }
+    }
+
+    async fn refresh(&self) -> Result<Tokens, RefreshError> {
+        let refresh_token = self
+            .tokens
+            .as_ref()
+            .and_then(|tokens| tokens.refresh_token.clone())
+            .ok_or_else(|| RefreshError::Rejected(anyhow!("Session expired")))?;
+
+        info!("🔄 Refreshing the access token");
+        let response = self
+            .config
+            .client()
+            .map_err(RefreshError::Failed)?
+            .exchange_refresh_token(&RefreshToken::new(refresh_token.clone()))
+            .request_async(async_http_client)
+            .await
+            .map_err(|err| match err {
+                RequestTokenError::ServerResponse(response)
+                    if *response.error() == BasicErrorResponseType::InvalidGrant =>
+                {
+                    RefreshError::Rejected(anyhow!("Session expired, refresh failed: {}", response))
+                }
+                err => RefreshError::Failed(anyhow!("Could not refresh the session: {}", err)),
+            })?;
+
+        let mut tokens = Tokens::from_response(&response);
+        // Providers may not rotate the refresh token
+        if tokens.refresh_token.is_none() {
+            tokens.refresh_token = Some(refresh_token);
+        }
+        Ok(tokens)
+    }
+}
+
+/// Why the tokens couldn't be refreshed
+enum RefreshError {
+    /// The provider turned the refresh token down, it's no use anymore
+    Rejected(anyhow::Error),
+    /// No answer from the provider, the tokens may still be good
+    Failed(anyhow::Error),
+}
+
+fn expires_soon(tokens: &Tokens) -> bool {
+    match tokens.expires_at {
+        Some(expires_at) => expires_at <= SystemTime::now() + REFRESH_MARGIN,
+        None => false,
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+
+use super::Tokens;
+
+const APP_DIR: &str = "churro-cli";
+const TOKENS_FILE: &str = "tokens.json";
+
+/// File backed token storage
+///
+/// The file is only readable by the current user (`0600`).
+#[derive(Debug, Clone)]
+pub struct TokenStore {
+    path: PathBuf,
+}
+
+impl TokenStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Store located in the user data dir, e.g. `~/.local/share/churro-cli/tokens.json`
+    pub fn default_location() -> Result<Self> {
+        let dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find the user data dir"))?;
+        Ok(Self::new(dir.join(APP_DIR).join(TOKENS_FILE)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stored tokens, `None` if nothing was saved yet
+    pub fn load(&self) -> Result<Option<Tokens>> {
+        let content = match fs::read(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).with_context(|| format!("Could not read {:?}", self.path)),
+        };
+        let tokens = serde_json::from_slice(&content)
+            .with_context(|| format!("Corrupted token file {:?}", self.path))?;
+        Ok(Some(tokens))
+    }
+
+    pub fn save(&self, tokens: &Tokens) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Could not create {:?}", dir))?;
+        }
+
+        // Write next to the target then rename, so a crash never leaves half a file
+        let tmp = self.path.with_extension("json.tmp");
+        let mut file = open_private(&tmp)?;
+        file.write_all(&serde_json::to_vec_pretty(tokens)?)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path).with_context(|| format!("Could not write {:?}", self.path))?;
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(err).with_context(|| format!("Could not remove {:?}", self.path))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn open_private(path: &Path) -> Result<fs::File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Could not open {:?}", path))?;
+    // `mode` is only applied on creation
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn open_private(path: &Path) -> Result<fs::File> {
+    fs::File::create(path).with_context(|| format!("Could not open {:?}", path))
+}
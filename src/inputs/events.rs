@@ -1,59 +1,138 @@
-use std::{
-    sync::{atomic::AtomicBool, atomic::Ordering, Arc},
-    time::Duration,
-};
+use std::time::Duration;
 
 use super::key::Key;
-use crossterm::event;
+use crossterm::event::{Event, EventStream, KeyEventKind};
+use futures::StreamExt;
+use tokio::task::JoinHandle;
 
 use super::InputEvent;
-use log::error;
+use log::{debug, error};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
 
+/// Terminal events, read asynchronously, and a tick every `tick_rate`
 pub struct Events {
     rx: tokio::sync::mpsc::Receiver<InputEvent>,
-    // Keep so doesn't get disposed sender-side
-    _tx: tokio::sync::mpsc::Sender<InputEvent>,
-    stop_capture: Arc<AtomicBool>,
+    task: JoinHandle<()>,
 }
 
 impl Events {
     pub fn new(tick_rate: Duration) -> Events {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let stop_capture = Arc::new(AtomicBool::new(false));
 
-        let event_tx = tx.clone();
-        let event_stop_capture = stop_capture.clone();
-        tokio::spawn(async move {
+        let task = tokio::spawn(async move {
+            let mut reader = EventStream::new();
+            let mut tick = tokio::time::interval(tick_rate);
+            let mut signals = Signals::new();
             loop {
-                if crossterm::event::poll(tick_rate).unwrap() {
-                    if let event::Event::Key(key) = event::read().unwrap() {
-                        let key = Key::from(key);
-                        if let Err(err) = event_tx.send(InputEvent::Input(key)).await {
-                            error!("Error sending key: {}", err);
+                let event = tokio::select! {
+                    _ = tick.tick() => InputEvent::Tick,
+                    event = signals.recv() => event,
+                    event = reader.next() => match event {
+                        Some(Ok(Event::Key(key))) if key.kind != KeyEventKind::Release => {
+                            InputEvent::Input(Key::from(key))
                         }
-                    }
-                }
-                if let Err(err) = event_tx.send(InputEvent::Tick).await {
-                    error!("Error sending tick: {}", err);
-                }
-                if event_stop_capture.load(Ordering::Relaxed) {
+                        Some(Ok(Event::Key(_))) => continue,
+                        Some(Ok(Event::Resize(width, height))) => InputEvent::Resize(width, height),
+                        Some(Ok(Event::Mouse(mouse))) => InputEvent::Mouse(mouse),
+                        Some(Ok(Event::Paste(text))) => InputEvent::Paste(text),
+                        Some(Ok(Event::FocusGained)) => InputEvent::FocusGained,
+                        Some(Ok(Event::FocusLost)) => InputEvent::FocusLost,
+                        Some(Err(err)) => InputEvent::Error(err),
+                        None => InputEvent::Error(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "terminal event stream closed",
+                        )),
+                    },
+                };
+                let stop = matches!(event, InputEvent::Error(_));
+                if tx.send(event).await.is_err() || stop {
+                    debug!("Stop capturing terminal events");
                     break;
                 }
             }
         });
 
-        Events {
-            rx,
-            _tx: tx,
-            stop_capture,
-        }
+        Events { rx, task }
     }
 
     pub async fn next(&mut self) -> InputEvent {
-        self.rx.recv().await.unwrap_or(InputEvent::Tick)
+        match self.rx.recv().await {
+            Some(event) => event,
+            None => InputEvent::Error(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "terminal events are no longer captured",
+            )),
+        }
     }
 
     pub fn close(&mut self) {
-        self.stop_capture.store(true, Ordering::Relaxed);
+        self.task.abort();
+    }
+}
+
+/// Process signals turned into input events: `SIGCONT` is `Resumed`,
+/// `SIGTERM` and `SIGHUP` are `Terminate`
+#[cfg(unix)]
+pub struct Signals {
+    cont: Option<Signal>,
+    term: Option<Signal>,
+    hup: Option<Signal>,
+}
+
+#[cfg(unix)]
+impl Signals {
+    pub fn new() -> Self {
+        let listen = |kind| match signal(kind) {
+            Ok(signal) => Some(signal),
+            Err(err) => {
+                error!("Could not listen to signal {:?}: {}", kind, err);
+                None
+            }
+        };
+        Self {
+            cont: listen(SignalKind::from_raw(libc::SIGCONT)),
+            term: listen(SignalKind::terminate()),
+            hup: listen(SignalKind::hangup()),
+        }
+    }
+
+    pub async fn recv(&mut self) -> InputEvent {
+        /// Waits forever without the signal, or once it can't be received anymore
+        async fn next(signal: &mut Option<Signal>) {
+            if let Some(received) = signal {
+                if received.recv().await.is_some() {
+                    return;
+                }
+                *signal = None;
+            }
+            std::future::pending().await
+        }
+
+        tokio::select! {
+            _ = next(&mut self.cont) => InputEvent::Resumed,
+            _ = next(&mut self.term) => InputEvent::Terminate,
+            _ = next(&mut self.hup) => InputEvent::Terminate,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub async fn recv(&mut self) -> InputEvent {
+        std::future::pending().await
+    }
+}
+
+impl Default for Signals {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -1,13 +1,31 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::BitOr,
+    str::FromStr,
+};
 
 use crossterm::event;
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 
-/// Represents an key.
+/// Represents a key: a key code and the modifiers held with it.
+///
+/// Chars carry their own case, so Shift is never set along a `Char`:
+/// Shift+a is `<A>` and Ctrl+Shift+a is `<Ctrl+A>`.
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
-pub enum Key {
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: Modifiers,
+}
+
+/// Key without its modifiers
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub enum KeyCode {
     /// Both Enter (or Return) and numpad Enter
     Enter,
-    /// Tabulation key
+    /// Tabulation key, Shift+Tab is `Tab` with `Modifiers::SHIFT`
     Tab,
     /// Backspace key
     Backspace,
@@ -36,169 +54,421 @@ pub enum Key {
     /// Page Down key
     PageDown,
 
-    /// F0 key
-    F0,
-    /// F1 key
-    F1,
-    /// F2 key
-    F2,
-    /// F3 key
-    F3,
-    /// F4 key
-    F4,
-    /// F5 key
-    F5,
-    /// F6 key
-    F6,
-    /// F7 key
-    F7,
-    /// F8 key
-    F8,
-    /// F9 key
-    F9,
-    /// F10 key
-    F10,
-    /// F11 key
-    F11,
-    /// F12 key
-    F12,
+    /// Function key, F1 is `F(1)`
+    F(u8),
     Char(char),
-    Ctrl(char),
-    Alt(char),
     Unknown,
 }
 
+/// Set of modifier keys
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers(0);
+    pub const CTRL: Modifiers = Modifiers(1);
+    pub const ALT: Modifiers = Modifiers(1 << 1);
+    pub const SHIFT: Modifiers = Modifiers(1 << 2);
+    pub const SUPER: Modifiers = Modifiers(1 << 3);
+    pub const HYPER: Modifiers = Modifiers(1 << 4);
+    pub const META: Modifiers = Modifiers(1 << 5);
+
+    /// Display order, with names
+    const NAMES: [(Modifiers, &'static str); 6] = [
+        (Modifiers::CTRL, "Ctrl"),
+        (Modifiers::ALT, "Alt"),
+        (Modifiers::SHIFT, "Shift"),
+        (Modifiers::SUPER, "Super"),
+        (Modifiers::HYPER, "Hyper"),
+        (Modifiers::META, "Meta"),
+    ];
+
+    pub const fn union(self, other: Modifiers) -> Modifiers {
+        Modifiers(self.0 | other.0)
+    }
+
+    pub const fn without(self, other: Modifiers) -> Modifiers {
+        Modifiers(self.0 & !other.0)
+    }
+
+    pub const fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    fn from_name(name: &str) -> Option<Modifiers> {
+        Self::NAMES
+            .iter()
+            .find(|(_, known)| known.eq_ignore_ascii_case(name))
+            .map(|(modifier, _)| *modifier)
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: Modifiers) -> Modifiers {
+        self.union(rhs)
+    }
+}
+
+impl From<event::KeyModifiers> for Modifiers {
+    fn from(modifiers: event::KeyModifiers) -> Self {
+        [
+            (event::KeyModifiers::CONTROL, Modifiers::CTRL),
+            (event::KeyModifiers::ALT, Modifiers::ALT),
+            (event::KeyModifiers::SHIFT, Modifiers::SHIFT),
+            (event::KeyModifiers::SUPER, Modifiers::SUPER),
+            (event::KeyModifiers::HYPER, Modifiers::HYPER),
+            (event::KeyModifiers::META, Modifiers::META),
+        ]
+        .into_iter()
+        .filter(|(crossterm, _)| modifiers.contains(*crossterm))
+        .fold(Modifiers::NONE, |all, (_, modifier)| all | modifier)
+    }
+}
+
 impl Key {
+    /// Key with modifiers, Shift is folded into chars
+    pub fn new(code: KeyCode, modifiers: Modifiers) -> Key {
+        match code {
+            KeyCode::Char(c) if modifiers.contains(Modifiers::SHIFT) => {
+                let mut upper = c.to_uppercase();
+                let c = match (upper.next(), upper.next()) {
+                    (Some(upper), None) => upper,
+                    _ => c,
+                };
+                Key {
+                    code: KeyCode::Char(c),
+                    modifiers: modifiers.without(Modifiers::SHIFT),
+                }
+            }
+            _ => Key { code, modifiers },
+        }
+    }
+
+    /// Char typed without modifier
+    pub const fn char(c: char) -> Key {
+        Key {
+            code: KeyCode::Char(c),
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    /// Ctrl+char
+    pub const fn ctrl(c: char) -> Key {
+        Key {
+            code: KeyCode::Char(c),
+            modifiers: Modifiers::CTRL,
+        }
+    }
+
+    /// Alt+char
+    pub const fn alt(c: char) -> Key {
+        Key {
+            code: KeyCode::Char(c),
+            modifiers: Modifiers::ALT,
+        }
+    }
+
+    /// Key without modifier
+    pub const fn plain(code: KeyCode) -> Key {
+        Key {
+            code,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
     /// If exit
     pub fn is_exit(&self) -> bool {
-        matches!(self, Key::Ctrl('c') | Key::Char('q') | Key::Esc)
+        *self == Key::ctrl('c') || *self == Key::char('q') || *self == Key::plain(KeyCode::Esc)
     }
 
     /// Returns the function key corresponding to the given number
     ///
     /// 1 -> F1, etc...
-    ///
-    /// # Panics
-    ///
-    /// If `n == 0 || n > 12`
-    pub fn from_f(n: u8) -> Key {
-        match n {
-            0 => Key::F0,
-            1 => Key::F1,
-            2 => Key::F2,
-            3 => Key::F3,
-            4 => Key::F4,
-            5 => Key::F5,
-            6 => Key::F6,
-            7 => Key::F7,
-            8 => Key::F8,
-            9 => Key::F9,
-            10 => Key::F10,
-            11 => Key::F11,
-            12 => Key::F12,
-            _ => panic!("unknown function key: F{}", n),
+    pub const fn from_f(n: u8) -> Key {
+        Key::plain(KeyCode::F(n))
+    }
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Self {
+        Key::plain(code)
+    }
+}
+
+impl Display for KeyCode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            _ => write!(f, "{:?}", self),
         }
     }
 }
 
 impl Display for Key {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match *self {
-            Key::Alt(' ') => write!(f, "<Alt+Space>"),
-            Key::Ctrl(' ') => write!(f, "<Ctrl+Space>"),
-            Key::Char(' ') => write!(f, "<Space>"),
-            Key::Alt(c) => write!(f, "<Alt+{}>", c),
-            Key::Ctrl(c) => write!(f, "<Ctrl+{}>", c),
-            Key::Char(c) => write!(f, "<{}>", c),
-            _ => write!(f, "<{:?}>", self),
+        write!(f, "<")?;
+        for (modifier, name) in Modifiers::NAMES.iter() {
+            if self.modifiers.contains(*modifier) {
+                write!(f, "{}+", name)?;
+            }
         }
+        write!(f, "{}>", self.code)
+    }
+}
+
+/// A key string that does not match any `Key`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError(String);
+
+impl Display for ParseKeyError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "invalid key `{}`", self.0)
     }
 }
 
+impl std::error::Error for ParseKeyError {}
+
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    /// Parse the `Display` form, e.g. `<Ctrl+c>`, `<Ctrl+Alt+Tab>`, `<F5>`, `<Space>`
+    ///
+    /// A single char can be written without brackets (`q`), and names are
+    /// case insensitive (`<ctrl+C>` is `<Ctrl+C>`, chars keep their case).
+    /// `<Shift+a>` is the same key as `<A>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseKeyError(s.to_string());
+        if let Some(c) = single_char(s) {
+            return Ok(Key::char(c));
+        }
+
+        let mut rest = s
+            .strip_prefix('<')
+            .and_then(|s| s.strip_suffix('>'))
+            .unwrap_or(s);
+        let mut modifiers = Modifiers::NONE;
+        // `<Ctrl++>` is Ctrl and the `+` char
+        while let Some((name, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            modifiers = modifiers | Modifiers::from_name(name).ok_or_else(err)?;
+            rest = key;
+        }
+
+        let code = if rest.eq_ignore_ascii_case("space") {
+            KeyCode::Char(' ')
+        } else if let Some(c) = single_char(rest) {
+            KeyCode::Char(c)
+        } else {
+            named_key(rest).ok_or_else(err)?
+        };
+        // Shift only makes sense on a char it changes, `<Shift+1>` is not `<1>`
+        if modifiers.contains(Modifiers::SHIFT)
+            && matches!(code, KeyCode::Char(c) if !c.is_lowercase())
+        {
+            return Err(err());
+        }
+        Ok(Key::new(code, modifiers))
+    }
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+impl Serialize for Key {
+    /// As its `Display` form, e.g. `"<Ctrl+c>"`
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                write!(f, "a key such as \"q\", \"<Ctrl+c>\" or \"<F5>\"")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Key, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
+
+fn named_key(name: &str) -> Option<KeyCode> {
+    let code = match name.to_ascii_lowercase().as_str() {
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "esc" => KeyCode::Esc,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "ins" => KeyCode::Ins,
+        "delete" => KeyCode::Delete,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "unknown" => KeyCode::Unknown,
+        fkey => KeyCode::F(fkey.strip_prefix('f')?.parse().ok()?),
+    };
+    Some(code)
+}
+
 impl From<event::KeyEvent> for Key {
     fn from(key_event: event::KeyEvent) -> Self {
-        match key_event {
-            event::KeyEvent {
-                code: event::KeyCode::Esc,
-                ..
-            } => Key::Esc,
-            event::KeyEvent {
-                code: event::KeyCode::Backspace,
-                ..
-            } => Key::Backspace,
-            event::KeyEvent {
-                code: event::KeyCode::Left,
-                ..
-            } => Key::Left,
-            event::KeyEvent {
-                code: event::KeyCode::Right,
-                ..
-            } => Key::Right,
-            event::KeyEvent {
-                code: event::KeyCode::Up,
-                ..
-            } => Key::Up,
-            event::KeyEvent {
-                code: event::KeyCode::Down,
-                ..
-            } => Key::Down,
-            event::KeyEvent {
-                code: event::KeyCode::Home,
-                ..
-            } => Key::Home,
-            event::KeyEvent {
-                code: event::KeyCode::End,
-                ..
-            } => Key::End,
-            event::KeyEvent {
-                code: event::KeyCode::PageUp,
-                ..
-            } => Key::PageUp,
-            event::KeyEvent {
-                code: event::KeyCode::PageDown,
-                ..
-            } => Key::PageDown,
-            event::KeyEvent {
-                code: event::KeyCode::Delete,
-                ..
-            } => Key::Delete,
-            event::KeyEvent {
-                code: event::KeyCode::Insert,
-                ..
-            } => Key::Ins,
-            event::KeyEvent {
-                code: event::KeyCode::F(n),
-                ..
-            } => Key::from_f(n),
-            event::KeyEvent {
-                code: event::KeyCode::Enter,
-                ..
-            } => Key::Enter,
-            event::KeyEvent {
-                code: event::KeyCode::Tab,
-                ..
-            } => Key::Tab,
-
-            // First check for char + modifier
-            event::KeyEvent {
-                code: event::KeyCode::Char(c),
-                modifiers: event::KeyModifiers::ALT,
-                kind: _,
-                state: _,
-            } => Key::Alt(c),
-            event::KeyEvent {
-                code: event::KeyCode::Char(c),
-                modifiers: event::KeyModifiers::CONTROL,
-                kind: _,
-                state: _,
-            } => Key::Ctrl(c),
-
-            event::KeyEvent {
-                code: event::KeyCode::Char(c),
-                ..
-            } => Key::Char(c),
-
-            _ => Key::Unknown,
+        let mut modifiers = Modifiers::from(key_event.modifiers);
+        let code = match key_event.code {
+            event::KeyCode::Esc => KeyCode::Esc,
+            event::KeyCode::Backspace => KeyCode::Backspace,
+            event::KeyCode::Left => KeyCode::Left,
+            event::KeyCode::Right => KeyCode::Right,
+            event::KeyCode::Up => KeyCode::Up,
+            event::KeyCode::Down => KeyCode::Down,
+            event::KeyCode::Home => KeyCode::Home,
+            event::KeyCode::End => KeyCode::End,
+            event::KeyCode::PageUp => KeyCode::PageUp,
+            event::KeyCode::PageDown => KeyCode::PageDown,
+            event::KeyCode::Delete => KeyCode::Delete,
+            event::KeyCode::Insert => KeyCode::Ins,
+            event::KeyCode::F(n) => KeyCode::F(n),
+            event::KeyCode::Enter => KeyCode::Enter,
+            event::KeyCode::Tab => KeyCode::Tab,
+            event::KeyCode::BackTab => {
+                modifiers = modifiers | Modifiers::SHIFT;
+                KeyCode::Tab
+            }
+            event::KeyCode::Char(c) => KeyCode::Char(c),
+            _ => KeyCode::Unknown,
+        };
+        Key::new(code, modifiers)
+    }
+}
+
+/// Keys pressed one after the other, e.g. `g g` or `<Ctrl+x> <Ctrl+s>`
+#[derive(PartialEq, Eq, Clone, Hash, Debug)]
+pub struct KeySequence(Vec<Key>);
+
+impl KeySequence {
+    pub fn keys(&self) -> &[Key] {
+        &self.0
+    }
+
+    /// This sequence followed by `key`, e.g. `<g>` then `<i>`
+    pub fn then(mut self, key: Key) -> Self {
+        self.0.push(key);
+        self
+    }
+
+    /// Whether `keys` are the first keys of this sequence, or all of them
+    pub fn starts_with(&self, keys: &[Key]) -> bool {
+        self.0.starts_with(keys)
+    }
+}
+
+impl From<Key> for KeySequence {
+    fn from(key: Key) -> Self {
+        KeySequence(vec![key])
+    }
+}
+
+impl TryFrom<Vec<Key>> for KeySequence {
+    type Error = EmptyKeySequence;
+
+    fn try_from(keys: Vec<Key>) -> Result<Self, Self::Error> {
+        if keys.is_empty() {
+            return Err(EmptyKeySequence);
+        }
+        Ok(KeySequence(keys))
+    }
+}
+
+/// A key sequence needs at least one key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmptyKeySequence;
+
+impl Display for EmptyKeySequence {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "empty key sequence")
+    }
+}
+
+impl std::error::Error for EmptyKeySequence {}
+
+impl Display for KeySequence {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (i, key) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", key)?;
         }
+        Ok(())
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = ParseKeyError;
+
+    /// Keys separated by spaces, e.g. `g g`, a lone key is parsed as such (` ` is `<Space>`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(key) = s.parse::<Key>() {
+            return Ok(key.into());
+        }
+        let keys = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<Key>, _>>()?;
+        if keys.is_empty() {
+            return Err(ParseKeyError(s.to_string()));
+        }
+        Ok(KeySequence(keys))
+    }
+}
+
+impl Serialize for KeySequence {
+    /// As its `Display` form, e.g. `"<Ctrl+x> <Ctrl+s>"`
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeySequence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeySequenceVisitor;
+
+        impl<'de> Visitor<'de> for KeySequenceVisitor {
+            type Value = KeySequence;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                write!(f, "keys such as \"q\", \"g g\" or \"<Ctrl+x> <Ctrl+s>\"")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<KeySequence, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(KeySequenceVisitor)
     }
 }
@@ -1,9 +1,25 @@
+use crossterm::event::MouseEvent;
+
 use self::key::Key;
 
 pub mod events;
 pub mod key;
 
+#[derive(Debug)]
 pub enum InputEvent {
     Input(Key),
     Tick,
+    /// New terminal size, width then height
+    Resize(u16, u16),
+    Mouse(MouseEvent),
+    /// Bracketed paste content
+    Paste(String),
+    FocusGained,
+    FocusLost,
+    /// The process was continued after being stopped (`SIGCONT`)
+    Resumed,
+    /// Asked to shut down (`SIGTERM`, `SIGHUP`)
+    Terminate,
+    /// Reading the terminal failed, no more events will follow
+    Error(std::io::Error),
 }
@@ -1,51 +1,342 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
-use crate::app::App;
-use anyhow::Result;
+use crate::{
+    api::{error::ApiError, retry::RetryNotifier, ApiClient, ApiConfig},
+    app::{
+        jobs::JobStatus,
+        modal::{Modal, ModalAnswer},
+        AppEvent,
+    },
+    auth::{login, session::Session, store::TokenStore, OAuthConfig},
+};
+use anyhow::{anyhow, Result};
+use tokio::{
+    sync::{
+        mpsc::{Receiver, UnboundedSender},
+        oneshot, Mutex, Semaphore,
+    },
+    task::JoinSet,
+};
 
-use super::IoEvent;
-use log::{error, info};
+use super::{IoEvent, IoJob, JobId, Settings};
+use log::{debug, error, info, warn};
 
+/// Runs IO events, cheap to clone so each job gets its own handle
+///
+/// Results are never applied to the `App` directly, they are sent back
+/// to the UI loop as `AppEvent`s.
+#[derive(Clone)]
 pub struct IoAsyncHandler {
-    app: Arc<tokio::sync::Mutex<App>>,
+    app_tx: UnboundedSender<AppEvent>,
+    session: Arc<Mutex<Option<Session>>>,
+    /// Replaced when the settings change
+    api: Arc<RwLock<Option<ApiClient>>>,
 }
 
 impl IoAsyncHandler {
-    pub fn new(app: Arc<tokio::sync::Mutex<App>>) -> Self {
-        Self { app }
+    pub fn new(app_tx: UnboundedSender<AppEvent>) -> Self {
+        let api = match ApiConfig::from_env().and_then(ApiClient::new) {
+            Ok(api) => Some(api),
+            Err(err) => {
+                warn!("API disabled: {}", err);
+                None
+            }
+        };
+        let api_url = api.as_ref().map(|api| api.config().base_url.clone());
+        let handler = Self {
+            app_tx,
+            session: Arc::new(Mutex::new(open_session())),
+            api: Arc::new(RwLock::new(api)),
+        };
+        handler.notify(AppEvent::ApiConfigured(api_url));
+        handler
     }
 
-    pub async fn handle_io_event(&mut self, io_event: IoEvent) {
-        let result = match io_event {
+    /// Process jobs until the channel closes
+    ///
+    /// Every job runs in its own task, at most `max_concurrency` at once.
+    /// Each job sends its own result as soon as it's done, then its final
+    /// status is reported, even if it panicked or was cancelled.
+    pub async fn run(self, mut rx: Receiver<IoJob>, max_concurrency: usize) {
+        let limit = Arc::new(Semaphore::new(max_concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        let mut running = HashMap::new();
+
+        loop {
+            tokio::select! {
+                Some(job) = rx.recv() => {
+                    let handler = self.clone();
+                    let limit = Arc::clone(&limit);
+                    let id = job.id;
+                    let abort = tasks.spawn(async move {
+                        // Answering can take a while, don't hold a permit meanwhile
+                        if !handler.confirm(&job.event).await {
+                            return JobStatus::Cancelled;
+                        }
+                        // The semaphore is never closed
+                        let _permit = limit.acquire_owned().await;
+                        handler.notify(AppEvent::JobStarted(id));
+                        match handler.handle_io_event(job).await {
+                            Ok(()) => JobStatus::Succeeded,
+                            Err(err) => JobStatus::Failed(err.to_string()),
+                        }
+                    });
+                    running.insert(abort.id(), id);
+                    self.notify(AppEvent::JobSpawned(id, abort));
+                }
+                Some(joined) = tasks.join_next_with_id(), if !tasks.is_empty() => {
+                    let (task_id, status) = match joined {
+                        Ok((task_id, status)) => (task_id, status),
+                        Err(err) if err.is_cancelled() => (err.id(), JobStatus::Cancelled),
+                        Err(err) => {
+                            error!("IO task panicked: {}", err);
+                            (err.id(), JobStatus::Failed(String::from("panicked")))
+                        }
+                    };
+                    if let Some(id) = running.remove(&task_id) {
+                        debug!("Job {} {}", id, status);
+                        self.notify(AppEvent::JobFinished(id, status));
+                    }
+                }
+                else => break,
+            }
+        }
+    }
+
+    pub async fn handle_io_event(&self, job: IoJob) -> Result<()> {
+        debug!("Starting job {}: {:?}", job.id, job.event);
+        let result = match job.event {
             IoEvent::Initialize => self.do_initialize().await,
             IoEvent::Sleep(duration) => self.do_sleep(duration).await,
+            IoEvent::Login => self.do_login().await,
+            IoEvent::FetchItems => self.do_fetch_items(job.id).await,
+            IoEvent::FetchItem(id) => self.do_fetch_item(job.id, id).await,
+            IoEvent::UpdateSettings(settings) => self.do_update_settings(settings).await,
         };
 
-        if let Err(err) = result {
-            error!("Error handling io event: {}", err);
+        if let Err(err) = &result {
+            error!("Error handling io event {}: {}", job.id, err);
         }
+        result
+    }
 
-        let mut app = self.app.lock().await;
-        app.loaded();
+    /// Ask what the job needs to know before it runs, false if it shouldn't
+    async fn confirm(&self, event: &IoEvent) -> bool {
+        match event {
+            IoEvent::Login => self.confirm_login().await,
+            _ => true,
+        }
+    }
+
+    async fn confirm_login(&self) -> bool {
+        let logged_in = self
+            .session
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(Session::is_logged_in);
+        if !logged_in {
+            return true;
+        }
+        let modal = Modal::confirm("Login", "Already logged in, log in again?");
+        if self.ask(modal).await == ModalAnswer::Confirmed {
+            return true;
+        }
+        info!("👍 Keeping the current session");
+        self.notify(AppEvent::LoggedIn);
+        false
     }
 
-    async fn do_initialize(&mut self) -> Result<()> {
+    async fn do_initialize(&self) -> Result<()> {
         info!("🚀 Initialize the application");
-        let mut app = self.app.lock().await;
+        let restored = match self.session.lock().await.as_mut() {
+            Some(session) => Some(session.restore().await),
+            None => None,
+        };
+
         tokio::time::sleep(Duration::from_secs(1)).await;
-        app.initialized(); // we could update the app state
+        match restored {
+            Some(Ok(())) => self.notify(AppEvent::LoggedIn),
+            Some(Err(err)) => {
+                warn!("🔒 {}", err);
+                self.notify(AppEvent::NeedsLogin(err.to_string()));
+            }
+            None => self.notify(AppEvent::Initialized), // we could update the app state
+        }
         info!("👍 Application initialized");
 
         Ok(())
     }
 
-    async fn do_sleep(&mut self, duration: Duration) -> Result<()> {
+    async fn do_sleep(&self, duration: Duration) -> Result<()> {
         info!("😴 Go to sleep for {:?}...", duration);
         tokio::time::sleep(duration).await; // Sleeping
         info!("⏰ Wake up !");
         // Notify the app for having slept
-        let mut app = self.app.lock().await;
-        app.slept();
+        self.notify(AppEvent::Slept);
+        Ok(())
+    }
+
+    async fn do_login(&self) -> Result<()> {
+        info!("🔐 Logging in...");
+        // Don't keep the session locked while the user is in the browser
+        let config = self
+            .session
+            .lock()
+            .await
+            .as_ref()
+            .map(|session| session.config().clone());
+        let result = match config {
+            Some(config) => match login::login(&config, login::open_browser).await {
+                Ok(tokens) => {
+                    if let Some(session) = self.session.lock().await.as_mut() {
+                        if let Err(err) = session.set_tokens(tokens) {
+                            warn!("Could not save the tokens: {}", err);
+                        }
+                    }
+                    Ok(())
+                }
+                Err(err) => Err(err),
+            },
+            None => Err(anyhow!("OAuth is not configured")),
+        };
+
+        match result {
+            Ok(()) => {
+                info!("👍 Logged in");
+                self.notify(AppEvent::LoggedIn);
+                Ok(())
+            }
+            Err(err) => {
+                self.notify(AppEvent::LoginFailed(err.to_string()));
+                Err(err)
+            }
+        }
+    }
+
+    async fn do_fetch_items(&self, job: JobId) -> Result<()> {
+        info!("📥 Fetching items...");
+        let token = self.access_token().await;
+        let result = match (self.api(job), token) {
+            (Ok(api), Ok(token)) => api.items(token.as_deref()).await,
+            (Err(err), _) | (_, Err(err)) => Err(err),
+        };
+
+        match result {
+            Ok(items) => {
+                info!("📦 Got {} items", items.len());
+                self.notify(AppEvent::ItemsLoaded(items));
+                Ok(())
+            }
+            Err(err) => {
+                self.notify(AppEvent::ApiFailed(err.clone()));
+                Err(err.into())
+            }
+        }
+    }
+
+    async fn do_fetch_item(&self, job: JobId, id: String) -> Result<()> {
+        info!("📥 Fetching item {}...", id);
+        let token = self.access_token().await;
+        let result = match (self.api(job), token) {
+            (Ok(api), Ok(token)) => api.item(&id, token.as_deref()).await,
+            (Err(err), _) | (_, Err(err)) => Err(err),
+        };
+
+        match result {
+            Ok(item) => {
+                self.notify(AppEvent::ItemLoaded(item));
+                Ok(())
+            }
+            Err(err) => {
+                self.notify(AppEvent::ApiFailed(err.clone()));
+                Err(err.into())
+            }
+        }
+    }
+
+    async fn do_update_settings(&self, settings: Settings) -> Result<()> {
+        info!("⚙️ Updating settings...");
+        let api = match &settings.api_url {
+            Some(url) => Some(ApiClient::new(ApiConfig::for_url(url.clone())?)?),
+            None => None,
+        };
+        if api.is_none() {
+            warn!("API disabled: no URL set");
+        }
+        *self.api.write().unwrap_or_else(|err| err.into_inner()) = api;
+        info!("👍 Settings updated");
+        self.notify(AppEvent::SettingsUpdated(settings));
         Ok(())
     }
+
+    fn notify(&self, event: AppEvent) {
+        if let Err(err) = self.app_tx.send(event) {
+            // The UI is gone, we're shutting down
+            debug!("Dropping app event: {:?}", err.0);
+        }
+    }
+
+    /// Show the modal and wait for the user to answer it
+    async fn ask(&self, modal: Modal) -> ModalAnswer {
+        let (tx, rx) = oneshot::channel();
+        self.notify(AppEvent::Ask(modal, tx));
+        // Dropped when the UI is gone
+        rx.await.unwrap_or(ModalAnswer::Cancelled)
+    }
+
+    /// The client as it is now, jobs already running keep the one they got
+    ///
+    /// Its retries are told to the app as the job's.
+    fn api(&self, job: JobId) -> Result<ApiClient, ApiError> {
+        let api = self
+            .api
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+            .ok_or_else(|| ApiError::Config(String::from("CHURRO_API_URL is not set")))?;
+        let app_tx = self.app_tx.clone();
+        let on_retry: RetryNotifier = Arc::new(move |notice| {
+            // Nobody to tell if the UI is gone
+            let _ = app_tx.send(AppEvent::Retrying(job, notice));
+        });
+        Ok(api.on_retry(on_retry))
+    }
+
+    /// Bearer token for the API, `None` when authentication is disabled
+    ///
+    /// The session stays locked during a refresh so concurrent jobs don't
+    /// all refresh at once.
+    async fn access_token(&self) -> Result<Option<String>, ApiError> {
+        match self.session.lock().await.as_mut() {
+            Some(session) => session
+                .access_token()
+                .await
+                .map(Some)
+                .map_err(|err| ApiError::Unauthenticated(err.to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Session backed by the default token store, `None` when OAuth is not configured
+fn open_session() -> Option<Session> {
+    let config = match OAuthConfig::from_env() {
+        Ok(config) => config,
+        Err(err) => {
+            warn!("Authentication disabled: {}", err);
+            return None;
+        }
+    };
+    match TokenStore::default_location() {
+        Ok(store) => Some(Session::new(config, store)),
+        Err(err) => {
+            warn!("Authentication disabled: {}", err);
+            None
+        }
+    }
 }
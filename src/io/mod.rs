@@ -1,9 +1,82 @@
-use std::time::Duration;
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+use log::warn;
+use url::Url;
 
 pub mod handler;
 
+/// IO jobs run concurrently by default, up to this many at once
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// How many jobs may run at once, from `CHURRO_IO_CONCURRENCY`
+pub fn concurrency_from_env() -> usize {
+    concurrency(std::env::var("CHURRO_IO_CONCURRENCY").ok().as_deref())
+}
+
+/// Parse a concurrency limit, the default when missing or not a positive number
+pub fn concurrency(value: Option<&str>) -> usize {
+    match value.map(|value| value.trim().parse::<usize>()) {
+        Some(Ok(limit)) if limit > 0 => limit,
+        Some(_) => {
+            warn!(
+                "Invalid CHURRO_IO_CONCURRENCY, running {} jobs at once",
+                DEFAULT_CONCURRENCY
+            );
+            DEFAULT_CONCURRENCY
+        }
+        None => DEFAULT_CONCURRENCY,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum IoEvent {
     Initialize,
     Sleep(Duration),
+    Login,
+    FetchItems,
+    FetchItem(String),
+    /// Apply what was saved in the settings form
+    UpdateSettings(Settings),
+}
+
+/// Settings the user can change while the app runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Sleep delay, in seconds
+    pub delay: u64,
+    /// `None` disables the API
+    pub api_url: Option<Url>,
+}
+
+impl Display for IoEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IoEvent::Initialize => write!(f, "Initialize"),
+            IoEvent::Sleep(duration) => write!(f, "Sleep {}s", duration.as_secs()),
+            IoEvent::Login => write!(f, "Login"),
+            IoEvent::FetchItems => write!(f, "Fetch items"),
+            IoEvent::FetchItem(id) => write!(f, "Fetch item {}", id),
+            IoEvent::UpdateSettings(_) => write!(f, "Update settings"),
+        }
+    }
+}
+
+/// Identify a dispatched `IoEvent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JobId(pub u64);
+
+impl Display for JobId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// An `IoEvent` on its way to the handler
+#[derive(Debug, Clone)]
+pub struct IoJob {
+    pub id: JobId,
+    pub event: IoEvent,
 }
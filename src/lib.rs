@@ -1,22 +1,33 @@
-use app::{App, AppReturn};
+use app::{App, AppEvent, AppReturn};
 use inputs::{events::Events, InputEvent};
 use io::IoEvent;
-use std::{sync::Arc, time::Duration};
+use std::time::Duration;
 
-use crate::app::ui;
+use crate::{
+    app::ui,
+    terminal::{Suspension, TerminalGuard},
+};
 use anyhow::Result;
 
 use tui::{backend::CrosstermBackend, Terminal};
 
+pub mod api;
 pub mod app;
+pub mod auth;
 pub mod inputs;
 pub mod io;
+pub mod logging;
+pub mod terminal;
 
-pub async fn start_ui(app: &Arc<tokio::sync::Mutex<App>>) -> Result<()> {
+pub async fn start_ui(
+    app: &mut App,
+    mut app_rx: tokio::sync::mpsc::UnboundedReceiver<AppEvent>,
+) -> Result<()> {
     // Crossterm backend config
     let stdout = std::io::stdout();
 
-    crossterm::terminal::enable_raw_mode()?;
+    // Restores the terminal when dropped, even on error or panic
+    let _guard = TerminalGuard::new()?;
 
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
@@ -26,31 +37,68 @@ pub async fn start_ui(app: &Arc<tokio::sync::Mutex<App>>) -> Result<()> {
     let tick_rate = Duration::from_millis(200);
     let mut events = Events::new(tick_rate);
 
-    {
-        let mut app = app.lock().await;
-        app.dispatch(IoEvent::Initialize).await;
-    }
+    let size = terminal.size()?;
+    app.on_resize(size.width, size.height);
+    app.dispatch(IoEvent::Initialize).await;
 
+    let mut suspension = Suspension::default();
+    let mut outcome = Ok(());
     loop {
-        let mut app = app.lock().await;
-
         // Render
-        terminal.draw(|rect| ui::draw(rect, &app))?;
-        // Handle inputs here
-        let result = match events.next().await {
-            InputEvent::Input(key) => app.do_action(key).await,
-            InputEvent::Tick => app.update_on_tick(),
+        terminal.draw(|rect| ui::draw(rect, &mut *app))?;
+        // Handle inputs and IO results here
+        let result = tokio::select! {
+            input = events.next() => match input {
+                InputEvent::Input(key) => app.do_action(key).await,
+                InputEvent::Tick => app.update_on_tick(),
+                InputEvent::Resize(width, height) => {
+                    app.on_resize(width, height);
+                    AppReturn::Continue
+                }
+                InputEvent::Mouse(mouse) => app.on_mouse(mouse).await,
+                InputEvent::Paste(text) => {
+                    app.on_paste(text);
+                    AppReturn::Continue
+                }
+                InputEvent::FocusGained => {
+                    app.on_focus(true);
+                    AppReturn::Continue
+                }
+                InputEvent::FocusLost => {
+                    app.on_focus(false);
+                    AppReturn::Continue
+                }
+                InputEvent::Resumed => {
+                    // Stopped by someone else, the screen is not ours anymore
+                    if suspension.resumed() {
+                        terminal::resume()?;
+                        terminal.clear()?;
+                    }
+                    AppReturn::Continue
+                }
+                // No questions asked, unlike quitting from the keyboard
+                InputEvent::Terminate => AppReturn::Exit,
+                InputEvent::Error(err) => {
+                    outcome = Err(err.into());
+                    AppReturn::Exit
+                }
+            },
+            Some(event) = app_rx.recv() => app.handle_event(event),
         };
 
-        if result == AppReturn::Exit {
-            events.close();
-            break;
+        match result {
+            AppReturn::Exit => {
+                events.close();
+                break;
+            }
+            AppReturn::Suspend => {
+                suspension.suspend()?;
+                // Full redraw, the shell used the screen meanwhile
+                terminal.clear()?;
+            }
+            AppReturn::Continue => {}
         }
     }
 
-    terminal.clear()?;
-    terminal.show_cursor()?;
-    crossterm::terminal::disable_raw_mode()?;
-
-    Ok(())
+    outcome
 }
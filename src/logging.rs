@@ -0,0 +1,87 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// How many records are kept for crash reports
+pub const RECENT_RECORDS: usize = 500;
+
+/// Crash reports get debug records, whatever level is shown
+const RECORDED: LevelFilter = LevelFilter::Debug;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+/// Level of the records shown in the log widget
+static SHOWN: Mutex<LevelFilter> = Mutex::new(LevelFilter::Info);
+static LOGGER: Logger = Logger;
+
+/// Forward records to the tui-logger widget, keeping the most recent ones around
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO);
+        let line = format!(
+            "{}.{:03} {:<5} {}: {}",
+            since_epoch.as_secs(),
+            since_epoch.subsec_millis(),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if let Ok(mut recent) = RECENT.lock() {
+            if recent.len() == RECENT_RECORDS {
+                recent.pop_front();
+            }
+            recent.push_back(line);
+        }
+        // The drain doesn't go through the tui-logger levels
+        if record.level() <= level() {
+            tui_logger::Drain::new().log(record);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger, in place of `tui_logger::init_logger`
+pub fn init(level: LevelFilter) -> Result<(), SetLoggerError> {
+    set_level(level);
+    log::set_logger(&LOGGER)
+}
+
+/// Show records up to that level, in place of `tui_logger::set_default_level`
+pub fn set_level(level: LevelFilter) {
+    if let Ok(mut shown) = SHOWN.lock() {
+        *shown = level;
+    }
+    log::set_max_level(level.max(RECORDED));
+}
+
+/// Level of the records shown
+pub fn level() -> LevelFilter {
+    match SHOWN.lock() {
+        Ok(shown) => *shown,
+        Err(poisoned) => *poisoned.into_inner(),
+    }
+}
+
+/// Most recent log lines, oldest first
+pub fn recent() -> Vec<String> {
+    match RECENT.lock() {
+        Ok(recent) => recent.iter().cloned().collect(),
+        // Still readable after a panic while logging
+        Err(poisoned) => poisoned.into_inner().iter().cloned().collect(),
+    }
+}
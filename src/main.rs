@@ -1,11 +1,10 @@
 use anyhow::Result;
 use churro_cli::{
-    app::App,
-    io::{handler::IoAsyncHandler, IoEvent},
-    start_ui,
+    app::{keymap::Keymap, App, AppEvent},
+    io::{self, handler::IoAsyncHandler, IoJob},
+    logging, start_ui, terminal,
 };
 use log::LevelFilter;
-use std::sync::Arc;
 extern crate dotenv;
 
 use dotenv::dotenv;
@@ -15,23 +14,35 @@ extern crate dotenv_codegen;
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
-    let (sync_io_tx, mut sync_io_rx) = tokio::sync::mpsc::channel::<IoEvent>(100);
+    // A broken keymap is reported in the app, with the defaults in use
+    let keymap_error = match Keymap::load() {
+        Ok(keymap) => {
+            keymap.install();
+            None
+        }
+        Err(err) => Some(err),
+    };
+    let (sync_io_tx, sync_io_rx) = tokio::sync::mpsc::channel::<IoJob>(100);
+    let (app_tx, app_rx) = tokio::sync::mpsc::unbounded_channel::<AppEvent>();
+
+    let mut app = App::new(sync_io_tx.clone());
 
-    let app = Arc::new(tokio::sync::Mutex::new(App::new(sync_io_tx.clone())));
-    let app_ui = Arc::clone(&app);
+    logging::init(LevelFilter::Debug).unwrap();
+    terminal::install_panic_hook();
 
-    tui_logger::init_logger(LevelFilter::Debug).unwrap();
-    tui_logger::set_default_level(LevelFilter::Debug);
+    if let Some(err) = keymap_error {
+        log::error!("{}", err);
+        app.diagnose(format!("{}\nUsing the default keys.", err));
+    }
 
     // Handle I/O
 
+    let concurrency = io::concurrency_from_env();
     tokio::spawn(async move {
-        let mut handler = IoAsyncHandler::new(app);
-        while let Some(io_event) = sync_io_rx.recv().await {
-            handler.handle_io_event(io_event).await;
-        }
+        let handler = IoAsyncHandler::new(app_tx);
+        handler.run(sync_io_rx, concurrency).await;
     });
 
-    start_ui(&app_ui).await?;
+    start_ui(&mut app, app_rx).await?;
     Ok(())
 }
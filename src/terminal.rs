@@ -0,0 +1,171 @@
+use std::{
+    backtrace::Backtrace,
+    fs,
+    io::{self, Write},
+    panic::PanicHookInfo,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use log::error;
+
+use crate::logging;
+
+/// Keeps the terminal in TUI mode (raw mode, alternate screen, mouse capture...)
+/// for as long as it lives, whatever way the UI exits
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    pub fn new() -> io::Result<Self> {
+        enable_raw_mode()?;
+        // From here on, failing restores what was done so far
+        let guard = Self { _private: () };
+        enter()?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if let Err(err) = restore() {
+            error!("Could not restore the terminal: {}", err);
+        }
+    }
+}
+
+fn enter() -> io::Result<()> {
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableBracketedPaste,
+        EnableMouseCapture,
+        EnableFocusChange,
+        Hide
+    )
+}
+
+/// Put the terminal back the way the shell expects it
+pub fn restore() -> io::Result<()> {
+    execute!(
+        io::stdout(),
+        DisableFocusChange,
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        LeaveAlternateScreen,
+        Show
+    )?;
+    disable_raw_mode()
+}
+
+/// Hand the terminal back to the shell and stop the process (`SIGTSTP`)
+///
+/// Returns once the process is continued, with the terminal in TUI mode
+/// again. The caller should redraw everything.
+pub fn suspend() -> io::Result<()> {
+    restore()?;
+    #[cfg(unix)]
+    // SAFETY: raising a signal has no memory safety implication
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+    resume()
+}
+
+/// Back into TUI mode, e.g. after being continued (`SIGCONT`)
+pub fn resume() -> io::Result<()> {
+    enable_raw_mode()?;
+    enter()
+}
+
+/// Tells the `SIGCONT` that follows our own suspend, which `suspend`
+/// already dealt with, from being continued after someone else stopped us
+#[derive(Debug, Default)]
+pub struct Suspension {
+    resuming: bool,
+}
+
+impl Suspension {
+    /// `suspend`, then expect its `SIGCONT`
+    pub fn suspend(&mut self) -> io::Result<()> {
+        suspend()?;
+        self.suspended();
+        Ok(())
+    }
+
+    /// We were suspended and resumed, the next `SIGCONT` comes from that
+    pub fn suspended(&mut self) {
+        // Only unix stops the process, and continues it
+        self.resuming = cfg!(unix);
+    }
+
+    /// Whether the terminal has to be taken back on `SIGCONT`
+    pub fn resumed(&mut self) -> bool {
+        !std::mem::take(&mut self.resuming)
+    }
+}
+
+/// Restore the terminal before reporting panics
+///
+/// The UI runs on the main thread: a panic there restores the terminal,
+/// prints the usual message and writes a crash report. IO jobs run on
+/// other threads and their panics are recovered, so the UI keeps going and
+/// only the crash report is written.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let on_ui_thread = std::thread::current().name() == Some("main");
+        if on_ui_thread {
+            let _ = restore();
+            default_hook(info);
+        }
+
+        match write_crash_report(info) {
+            Ok(path) if on_ui_thread => {
+                eprintln!("Crash report written to {}", path.display())
+            }
+            Ok(path) => error!("Crash report written to {}", path.display()),
+            Err(err) if on_ui_thread => eprintln!("Could not write a crash report: {}", err),
+            Err(err) => error!("Could not write a crash report: {}", err),
+        }
+    }));
+}
+
+fn write_crash_report(info: &PanicHookInfo) -> io::Result<PathBuf> {
+    let dir = dirs::state_dir()
+        .or_else(dirs::data_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("churro-cli");
+    fs::create_dir_all(&dir)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs())
+        .unwrap_or_default();
+    let path = dir.join(format!("crash-{}.log", timestamp));
+
+    let mut file = fs::File::create(&path)?;
+    writeln!(file, "churro-cli {} crashed", env!("CARGO_PKG_VERSION"))?;
+    writeln!(
+        file,
+        "thread: {}",
+        std::thread::current().name().unwrap_or("<unnamed>")
+    )?;
+    writeln!(file, "{}", info)?;
+    writeln!(file, "\nBacktrace:\n{}", Backtrace::force_capture())?;
+    writeln!(file, "\nRecent logs:")?;
+    for line in logging::recent() {
+        writeln!(file, "{}", line)?;
+    }
+
+    Ok(path)
+}
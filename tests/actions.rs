@@ -0,0 +1,64 @@
+use churro_cli::{
+    app::actions::{conflicts, Action, Actions, KeyConflict, KeyMatch},
+    inputs::key::{Key, KeySequence},
+};
+
+fn keys(s: &str) -> KeySequence {
+    s.parse().unwrap()
+}
+
+#[test]
+fn default_bindings_do_not_conflict() {
+    let actions = Actions::try_from(Action::iterator().copied().collect::<Vec<_>>()).unwrap();
+    assert_eq!(
+        actions.find(&[Key::char('q')]),
+        KeyMatch::Action(Action::Quit)
+    );
+    assert_eq!(actions.find(&[Key::char('x')]), KeyMatch::None);
+    assert!(actions.continuations(&[Key::char('q')]).is_empty());
+}
+
+#[test]
+fn detects_shared_and_shadowed_sequences() {
+    let (g, gg, gl, q) = (keys("g"), keys("g g"), keys("g l"), keys("q"));
+    assert!(conflicts([
+        (&gg, Action::Sleep),
+        (&gl, Action::Login),
+        (&q, Action::Quit)
+    ])
+    .is_empty());
+    // The same action can be bound twice
+    assert!(conflicts([(&g, Action::Quit), (&g, Action::Quit)]).is_empty());
+
+    assert_eq!(
+        conflicts([(&gg, Action::Sleep), (&gg, Action::Login)]),
+        vec![KeyConflict::Shared {
+            keys: gg.clone(),
+            actions: vec![Action::Sleep, Action::Login]
+        }]
+    );
+    let shadowed = conflicts([(&gg, Action::Sleep), (&g, Action::Login)]);
+    assert_eq!(
+        shadowed,
+        vec![KeyConflict::Shadowed {
+            keys: gg.clone(),
+            action: Action::Sleep,
+            prefix: g.clone(),
+            by: Action::Login
+        }]
+    );
+    assert_eq!(
+        shadowed[0].to_string(),
+        "<g> <g> (Sleep) is shadowed by <g> (Login)"
+    );
+}
+
+#[test]
+fn actions_are_read_by_name() {
+    for action in Action::iterator() {
+        let json = format!("\"{}\"", action.name());
+        assert_eq!(serde_json::from_str::<Action>(&json).unwrap(), *action);
+    }
+    assert_eq!(Action::FetchItems.name(), "fetch-items");
+    assert!(serde_json::from_str::<Action>("\"FetchItems\"").is_err());
+}
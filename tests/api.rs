@@ -0,0 +1,85 @@
+use std::{convert::Infallible, net::SocketAddr, time::Duration};
+
+use churro_cli::api::{error::ApiError, retry::RetryPolicy, ApiClient, ApiConfig};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response,
+};
+use url::Url;
+
+/// Stand-in backend, every item is named after the path and query it was
+/// requested with
+async fn backend(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let requested = req.uri().path_and_query().unwrap().as_str();
+    let body = serde_json::json!({ "id": "1", "name": requested }).to_string();
+    Ok(Response::builder()
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap())
+}
+
+fn start_backend() -> SocketAddr {
+    let make_svc = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(backend)) });
+    let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+fn client(base_url: &str) -> ApiClient {
+    ApiClient::new(ApiConfig {
+        base_url: Url::parse(base_url).unwrap(),
+        timeout: Duration::from_secs(5),
+        retry: RetryPolicy::none(),
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn item_ids_stay_in_their_path_segment() {
+    let backend = start_backend();
+    let api = client(&format!("http://{}/api", backend));
+
+    for (id, requested) in [
+        ("42", "/api/items/42"),
+        ("../admin", "/api/items/..%2Fadmin"),
+        ("a/b", "/api/items/a%2Fb"),
+        ("x?y=1", "/api/items/x%3Fy=1"),
+        ("#frag", "/api/items/%23frag"),
+        ("%2e%2e", "/api/items/%252e%252e"),
+    ] {
+        let item = api.item(id, None).await.unwrap();
+        assert_eq!(item.name, requested, "id {}", id);
+    }
+}
+
+#[tokio::test]
+async fn base_url_slash_is_optional() {
+    let backend = start_backend();
+    for base_url in [
+        format!("http://{}/api", backend),
+        format!("http://{}/api/", backend),
+    ] {
+        let url = client(&base_url).url(&["items", "1"]).unwrap();
+        assert_eq!(url.path(), "/api/items/1");
+    }
+    let url = client(&format!("http://{}", backend))
+        .url(&["items"])
+        .unwrap();
+    assert_eq!(url.path(), "/items");
+}
+
+#[tokio::test]
+async fn dot_segments_are_rejected() {
+    let backend = start_backend();
+    let api = client(&format!("http://{}/api", backend));
+    for id in ["", ".", ".."] {
+        let err = api.item(id, None).await.unwrap_err();
+        assert!(
+            matches!(err, ApiError::InvalidArgument(_)),
+            "id {:?}: {}",
+            id,
+            err
+        );
+    }
+}
@@ -0,0 +1,594 @@
+use std::time::Duration;
+
+use churro_cli::{
+    api::{error::ApiError, models::Item, retry::RetryNotice},
+    app::{
+        actions::Action,
+        input::TextInput,
+        jobs::JobStatus,
+        modal::{Modal, ModalAnswer},
+        screens::ScreenId,
+        ui::{self, FULL_HEIGHT, FULL_WIDTH},
+        App, AppEvent, AppReturn, InputMode, SEQUENCE_TIMEOUT,
+    },
+    inputs::key::{Key, KeyCode, Modifiers},
+    io::{IoEvent, IoJob, Settings},
+    logging,
+};
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use log::LevelFilter;
+use tokio::sync::{mpsc, oneshot};
+use tui::{backend::TestBackend, layout::Rect, Terminal};
+
+fn initialized_app() -> (App, mpsc::Receiver<IoJob>) {
+    let (io_tx, io_rx) = mpsc::channel(100);
+    let mut app = App::new(io_tx);
+    app.initialized();
+    (app, io_rx)
+}
+
+async fn press(app: &mut App, keys: &str) -> AppReturn {
+    let mut result = AppReturn::Continue;
+    for c in keys.chars() {
+        result = app.do_action(Key::char(c)).await;
+    }
+    result
+}
+
+fn delay(app: &App) -> u64 {
+    app.state().duration().unwrap().as_secs()
+}
+
+/// Sleep jobs dispatched since the last call
+fn sleeps(io_rx: &mut mpsc::Receiver<IoJob>) -> usize {
+    std::iter::from_fn(|| io_rx.try_recv().ok())
+        .filter(|job| matches!(job.event, IoEvent::Sleep(_)))
+        .count()
+}
+
+/// Run the action picked in the palette with the count typed before
+async fn palette_with_count(app: &mut App, count: &str, query: &str) {
+    press(app, count).await;
+    press_key(app, Key::ctrl('p')).await;
+    press(app, query).await;
+    press_key(app, Key::plain(KeyCode::Enter)).await;
+}
+
+#[tokio::test]
+async fn count_runs_the_action_that_many_times() {
+    let (mut app, _io_rx) = initialized_app();
+    press(&mut app, "3+").await;
+    assert_eq!(delay(&app), 4);
+    assert_eq!(app.count(), None);
+
+    // Multiple digits, as far as the delay goes
+    press(&mut app, "12+").await;
+    assert_eq!(delay(&app), 10);
+
+    // Picked in the palette, the count typed before it goes along
+    palette_with_count(&mut app, "4", "decr").await;
+    assert_eq!(delay(&app), 6);
+}
+
+#[tokio::test]
+async fn count_is_ignored_by_actions_without_one() {
+    let (mut app, mut io_rx) = initialized_app();
+    press(&mut app, "4f").await;
+    let job = io_rx.try_recv().unwrap();
+    assert!(matches!(job.event, IoEvent::FetchItems));
+    assert!(io_rx.try_recv().is_err());
+
+    // Not even sleeps, a count would tie up the IO tasks for long
+    press(&mut app, "99s").await;
+    assert_eq!(sleeps(&mut io_rx), 1);
+}
+
+#[tokio::test]
+async fn delay_stays_within_bounds() {
+    let (mut app, _io_rx) = initialized_app();
+    press(&mut app, "3+-").await;
+    assert_eq!(delay(&app), 3);
+    press(&mut app, "9-").await;
+    assert_eq!(delay(&app), 1);
+    press(&mut app, "20+").await;
+    assert_eq!(delay(&app), 10);
+}
+
+#[tokio::test]
+async fn zero_only_continues_a_count() {
+    let (mut app, _io_rx) = initialized_app();
+    press(&mut app, "0").await;
+    assert_eq!(app.count(), None);
+    press(&mut app, "10").await;
+    assert_eq!(app.count(), Some(10));
+}
+
+#[tokio::test]
+async fn count_times_out_like_pending_keys() {
+    let (mut app, _io_rx) = initialized_app();
+    press(&mut app, "4").await;
+    app.update_on_tick();
+    assert_eq!(app.count(), Some(4));
+    std::thread::sleep(SEQUENCE_TIMEOUT);
+    app.update_on_tick();
+    assert_eq!(app.count(), None);
+}
+
+#[tokio::test]
+async fn repeat_replays_the_last_action_and_count() {
+    let (mut app, mut io_rx) = initialized_app();
+    assert_eq!(press(&mut app, ".").await, AppReturn::Continue);
+
+    press(&mut app, "2+.").await;
+    assert_eq!(delay(&app), 5);
+    // A new count replaces the old one
+    press(&mut app, "3.").await;
+    assert_eq!(delay(&app), 8);
+    press(&mut app, "s.").await;
+    assert_eq!(sleeps(&mut io_rx), 2);
+
+    // From the command line too
+    press(&mut app, ":increment-delay 1").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    press(&mut app, ".").await;
+    assert_eq!(delay(&app), 10);
+}
+
+#[tokio::test]
+async fn quit_is_not_repeated() {
+    let (mut app, _io_rx) = initialized_app();
+    press(&mut app, ":increment-delay").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(press(&mut app, "5q").await, AppReturn::Exit);
+    press(&mut app, ".").await;
+    assert_eq!(delay(&app), 3);
+}
+
+async fn press_key(app: &mut App, key: Key) -> AppReturn {
+    app.do_action(key).await
+}
+
+#[tokio::test]
+async fn palette_runs_the_selected_action() {
+    let (mut app, _io_rx) = initialized_app();
+    press_key(&mut app, Key::ctrl('p')).await;
+    press(&mut app, "incr").await;
+    assert_eq!(app.palette().map(|palette| palette.query()), Some("incr"));
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert!(app.palette().is_none());
+    assert_eq!(delay(&app), 2);
+
+    // With the count typed before opening it, and repeatable
+    press(&mut app, "3").await;
+    press_key(&mut app, Key::ctrl('p')).await;
+    press(&mut app, "inc").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(delay(&app), 5);
+    press(&mut app, ".").await;
+    assert_eq!(delay(&app), 8);
+}
+
+#[tokio::test]
+async fn palette_keeps_keys_from_actions() {
+    let (mut app, _io_rx) = initialized_app();
+    // q is part of the query, not Quit
+    press_key(&mut app, Key::ctrl('p')).await;
+    assert_eq!(press(&mut app, "q").await, AppReturn::Continue);
+    press_key(&mut app, Key::plain(KeyCode::Esc)).await;
+    assert!(app.palette().is_none());
+    assert_eq!(press(&mut app, "q").await, AppReturn::Exit);
+}
+
+#[tokio::test]
+async fn command_line_runs_commands() {
+    let (mut app, mut io_rx) = initialized_app();
+    press(&mut app, ":delay 7").await;
+    assert!(app.command_line().is_open());
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert!(!app.command_line().is_open());
+    assert_eq!(delay(&app), 7);
+
+    press(&mut app, ":sleep 3").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    let job = io_rx.try_recv().unwrap();
+    assert!(matches!(job.event, IoEvent::Sleep(d) if d == Duration::from_secs(3)));
+
+    press(&mut app, ":increment-delay 2").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(delay(&app), 9);
+
+    // The sleep is still running, so quitting asks first
+    press(&mut app, ":quit").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert!(app.modal().is_some());
+    assert_eq!(press(&mut app, "y").await, AppReturn::Exit);
+}
+
+#[tokio::test]
+async fn command_line_keeps_errors_inline() {
+    let (mut app, _io_rx) = initialized_app();
+    press(&mut app, ":delay 70").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert!(app.command_line().is_open());
+    assert_eq!(
+        app.command_line().error(),
+        Some("Invalid argument `70`, expected seconds between 1 and 10")
+    );
+    // Editing clears the error
+    press_key(&mut app, Key::plain(KeyCode::Backspace)).await;
+    assert_eq!(app.command_line().error(), None);
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(delay(&app), 7);
+}
+
+#[tokio::test]
+async fn command_line_needs_the_action_available() {
+    let (io_tx, _io_rx) = mpsc::channel(100);
+    let mut app = App::new(io_tx);
+    app.needs_login(String::from("no session"));
+    press(&mut app, ":fetch-items").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(
+        app.command_line().error(),
+        Some("Not available here: fetch-items")
+    );
+
+    // Asking for the argument first doesn't get around it
+    for (command, name) in [(":fetch-item", "fetch-item"), (":delay", "delay")] {
+        press_key(&mut app, Key::ctrl('u')).await;
+        press(&mut app, command).await;
+        press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+        assert!(app.modal().is_none());
+        assert_eq!(
+            app.command_line().error(),
+            Some(format!("Not available here: {}", name).as_str())
+        );
+    }
+}
+
+fn screen_ids(app: &App) -> Vec<ScreenId> {
+    app.screens().map(|screen| screen.id()).collect()
+}
+
+#[tokio::test]
+async fn navigates_between_screens() {
+    let (mut app, _io_rx) = initialized_app();
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard]);
+    assert!(!app.actions().actions().contains(&Action::Back));
+
+    press(&mut app, "gi").await;
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard, ScreenId::Items]);
+    assert!(app.actions().actions().contains(&Action::Back));
+    // Dashboard actions are gone
+    assert!(!app.actions().actions().contains(&Action::Sleep));
+    press(&mut app, "gi").await;
+    assert_eq!(app.screens().count(), 2);
+
+    press_key(&mut app, Key::plain(KeyCode::Esc)).await;
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard]);
+    press_key(&mut app, Key::plain(KeyCode::Esc)).await;
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard]);
+    assert!(app.actions().actions().contains(&Action::Sleep));
+}
+
+#[tokio::test]
+async fn login_replaces_the_screens() {
+    let (mut app, _io_rx) = initialized_app();
+    press(&mut app, "gi").await;
+    app.needs_login(String::from("session expired"));
+    assert_eq!(screen_ids(&app), [ScreenId::Login]);
+    assert!(app.actions().actions().contains(&Action::Login));
+    assert!(!app.actions().actions().contains(&Action::FetchItems));
+
+    app.logged_in();
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard]);
+    assert!(app.actions().actions().contains(&Action::FetchItems));
+}
+
+#[tokio::test]
+async fn quit_asks_while_jobs_are_running() {
+    let (mut app, _io_rx) = initialized_app();
+    assert_eq!(press(&mut app, "q").await, AppReturn::Exit);
+
+    press(&mut app, "f").await;
+    assert_eq!(press(&mut app, "q").await, AppReturn::Continue);
+    assert_eq!(
+        app.modal().map(Modal::message),
+        Some("1 job is still running, quit anyway?")
+    );
+    // Keys go to the modal, and no is the default
+    assert_eq!(press(&mut app, "i").await, AppReturn::Continue);
+    assert_eq!(delay(&app), 1);
+    assert_eq!(
+        press_key(&mut app, Key::plain(KeyCode::Enter)).await,
+        AppReturn::Continue
+    );
+    assert!(app.modal().is_none());
+
+    press(&mut app, "f").await;
+    press(&mut app, "q").await;
+    assert_eq!(
+        app.modal().map(Modal::message),
+        Some("2 jobs are still running, quit anyway?")
+    );
+    press_key(&mut app, Key::plain(KeyCode::Left)).await;
+    assert_eq!(
+        press_key(&mut app, Key::plain(KeyCode::Enter)).await,
+        AppReturn::Exit
+    );
+}
+
+#[tokio::test]
+async fn missing_arguments_are_asked_for() {
+    let (mut app, mut io_rx) = initialized_app();
+    press(&mut app, ":delay").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert!(!app.command_line().is_open());
+    assert_eq!(app.modal().map(Modal::title), Some(":delay"));
+    press(&mut app, "4").await;
+    assert!(app.modal().is_none());
+    assert_eq!(delay(&app), 4);
+
+    press(&mut app, ":log-level").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    press(&mut app, "jj").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(logging::level(), LevelFilter::Warn);
+
+    press(&mut app, ":fetch-item").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    press(&mut app, "abc").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    let job = io_rx.try_recv().unwrap();
+    assert!(matches!(job.event, IoEvent::FetchItem(id) if id == "abc"));
+
+    // Cancelled, nothing runs
+    press(&mut app, ":fetch-item").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    press_key(&mut app, Key::plain(KeyCode::Esc)).await;
+    assert!(app.modal().is_none());
+    assert!(io_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn modals_answer_the_io_task_in_turn() {
+    let (mut app, _io_rx) = initialized_app();
+    let (first_tx, first_rx) = oneshot::channel();
+    let (second_tx, second_rx) = oneshot::channel();
+    app.handle_event(AppEvent::Ask(Modal::confirm("First", "?"), first_tx));
+    app.handle_event(AppEvent::Ask(Modal::text("Second", "?"), second_tx));
+
+    assert_eq!(app.modal().map(Modal::title), Some("First"));
+    press(&mut app, "y").await;
+    assert_eq!(first_rx.await, Ok(ModalAnswer::Confirmed));
+
+    assert_eq!(app.modal().map(Modal::title), Some("Second"));
+    app.on_paste(String::from("pasted\nignored"));
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(
+        second_rx.await,
+        Ok(ModalAnswer::Text(String::from("pasted")))
+    );
+}
+
+#[tokio::test]
+async fn input_mode_sends_keys_to_the_field() {
+    let (mut app, _io_rx) = initialized_app();
+    assert_eq!(app.input_mode(), InputMode::Normal);
+    press(&mut app, ":delay 3").await;
+    assert_eq!(app.input_mode(), InputMode::Editing);
+
+    // Word editing, and bound keys are just text
+    press_key(&mut app, Key::ctrl('w')).await;
+    press(&mut app, "7").await;
+    press_key(&mut app, Key::new(KeyCode::Left, Modifiers::CTRL)).await;
+    press(&mut app, "q").await;
+    assert_eq!(app.command_line().input(), "delay q7");
+    press_key(&mut app, Key::plain(KeyCode::Backspace)).await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(delay(&app), 7);
+    assert_eq!(app.input_mode(), InputMode::Normal);
+
+    // Up still goes through history, Backspace on the empty line leaves it
+    press(&mut app, ":").await;
+    press_key(&mut app, Key::plain(KeyCode::Up)).await;
+    assert_eq!(app.command_line().input(), "delay 7");
+    press_key(&mut app, Key::ctrl('u')).await;
+    press_key(&mut app, Key::plain(KeyCode::Backspace)).await;
+    assert!(!app.command_line().is_open());
+}
+
+#[tokio::test]
+async fn settings_form_updates_the_delay_and_api() {
+    let (mut app, mut io_rx) = initialized_app();
+    press(&mut app, "gs").await;
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard, ScreenId::Settings]);
+    assert_eq!(app.input_mode(), InputMode::Editing);
+
+    // The delay field has the focus, q is swallowed rather than quitting
+    press_key(&mut app, Key::plain(KeyCode::Backspace)).await;
+    assert_eq!(press(&mut app, "q4").await, AppReturn::Continue);
+    press_key(&mut app, Key::plain(KeyCode::Tab)).await;
+    press(&mut app, "localhost").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert!(io_rx.try_recv().is_err());
+    let form = app.screen().form().unwrap();
+    assert_eq!(form.focused(), 1);
+    assert!(form.fields()[1].error().is_some());
+
+    press_key(&mut app, Key::ctrl('u')).await;
+    press(&mut app, "http://localhost:8080").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    let settings = match io_rx.try_recv().unwrap().event {
+        IoEvent::UpdateSettings(settings) => settings,
+        other => panic!("unexpected job: {:?}", other),
+    };
+    assert_eq!(settings.delay, 4);
+    assert_eq!(
+        settings.api_url.as_ref().map(|url| url.as_str()),
+        Some("http://localhost:8080/")
+    );
+
+    // Applied once the IO handler is done
+    assert_eq!(delay(&app), 1);
+    app.handle_event(AppEvent::SettingsUpdated(settings.clone()));
+    assert_eq!(delay(&app), 4);
+    assert_eq!(app.api_url(), settings.api_url.as_ref());
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard]);
+}
+
+#[tokio::test]
+async fn settings_form_is_left_with_back() {
+    let (mut app, _io_rx) = initialized_app();
+    app.handle_event(AppEvent::ApiConfigured(None));
+    press(&mut app, "gs").await;
+    press_key(&mut app, Key::plain(KeyCode::Esc)).await;
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard]);
+    let unchanged = Settings {
+        delay: 1,
+        api_url: None,
+    };
+    app.handle_event(AppEvent::SettingsUpdated(unchanged));
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard]);
+}
+
+#[tokio::test]
+async fn items_table_fetches_the_marked_items() {
+    let (mut app, mut io_rx) = initialized_app();
+    let item = |id: &str, name: &str| Item {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: None,
+    };
+    app.handle_event(AppEvent::ItemsLoaded(vec![
+        item("a", "zeta"),
+        item("b", "alpha"),
+        item("c", "mu"),
+    ]));
+    press(&mut app, "gi").await;
+    assert_eq!(screen_ids(&app), [ScreenId::Dashboard, ScreenId::Items]);
+
+    let fetched = |io_rx: &mut mpsc::Receiver<IoJob>| {
+        std::iter::from_fn(|| io_rx.try_recv().ok())
+            .filter_map(|job| match job.event {
+                IoEvent::FetchItem(id) => Some(id),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The selected one when none are marked
+    press(&mut app, "j").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(fetched(&mut io_rx), ["b"]);
+
+    // Sorted by name, the marked ones in that order
+    press(&mut app, "lok j ").await;
+    press_key(&mut app, Key::plain(KeyCode::Enter)).await;
+    assert_eq!(fetched(&mut io_rx), ["b", "c"]);
+
+    // The keymap still gets the other keys
+    press(&mut app, "f").await;
+    assert!(matches!(
+        io_rx.try_recv().unwrap().event,
+        IoEvent::FetchItems
+    ));
+}
+
+/// Fetch the selected item, its ID
+async fn fetched(app: &mut App, io_rx: &mut mpsc::Receiver<IoJob>) -> String {
+    press_key(app, Key::plain(KeyCode::Enter)).await;
+    match io_rx.try_recv().unwrap().event {
+        IoEvent::FetchItem(id) => id,
+        other => panic!("unexpected job: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn items_table_follows_the_mouse() {
+    let (mut app, mut io_rx) = initialized_app();
+    let item = |id: &str| Item {
+        id: id.to_string(),
+        name: id.to_uppercase(),
+        description: None,
+    };
+    app.handle_event(AppEvent::ItemsLoaded(vec![item("a"), item("b"), item("c")]));
+    press(&mut app, "gi").await;
+    app.on_resize(FULL_WIDTH, FULL_HEIGHT);
+    let mut terminal = Terminal::new(TestBackend::new(FULL_WIDTH, FULL_HEIGHT)).unwrap();
+    terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+
+    let areas = ui::areas(Rect::new(0, 0, FULL_WIDTH, FULL_HEIGHT)).unwrap();
+    let mouse = |kind, area: Rect, row| MouseEvent {
+        kind,
+        column: area.x + 2,
+        row,
+        modifiers: KeyModifiers::NONE,
+    };
+    // The second row, below the border, the header and the first one
+    let click = MouseEventKind::Down(MouseButton::Left);
+    app.on_mouse(mouse(click, areas.body, areas.body.y + 3))
+        .await;
+    assert_eq!(fetched(&mut app, &mut io_rx).await, "b");
+    app.on_mouse(mouse(
+        MouseEventKind::ScrollDown,
+        areas.body,
+        areas.body.y + 3,
+    ))
+    .await;
+    assert_eq!(fetched(&mut app, &mut io_rx).await, "c");
+
+    // Nothing below the rows, and the wheel over the help scrolls the help
+    app.on_mouse(mouse(click, areas.body, areas.body.y + 6))
+        .await;
+    let help = areas.help.unwrap();
+    app.on_mouse(mouse(MouseEventKind::ScrollUp, help, help.y + 3))
+        .await;
+    assert_eq!(fetched(&mut app, &mut io_rx).await, "c");
+}
+
+#[tokio::test]
+async fn paste_goes_to_the_focused_field() {
+    let (mut app, _io_rx) = initialized_app();
+    // Nowhere to go
+    app.on_paste(String::from("ignored"));
+
+    press(&mut app, "gs").await;
+    press_key(&mut app, Key::plain(KeyCode::Tab)).await;
+    press_key(&mut app, Key::ctrl('u')).await;
+    app.on_paste(String::from("http://localhost:8080\nrest"));
+    let form = app.screen().form().unwrap();
+    assert_eq!(
+        form.input().map(TextInput::value),
+        Some("http://localhost:8080")
+    );
+}
+
+#[tokio::test]
+async fn retry_notice_goes_with_its_job() {
+    let (mut app, mut io_rx) = initialized_app();
+    press(&mut app, "ff").await;
+    let first = io_rx.try_recv().unwrap().id;
+    let second = io_rx.try_recv().unwrap().id;
+    let notice = RetryNotice {
+        attempt: 2,
+        max_attempts: 4,
+        delay: Duration::from_secs(1),
+        error: ApiError::Timeout,
+    };
+
+    app.handle_event(AppEvent::Retrying(first, notice.clone()));
+    assert!(app.retry().is_some());
+    // Another job is done, the first one is still retrying
+    app.handle_event(AppEvent::JobFinished(second, JobStatus::Succeeded));
+    assert!(app.retry().is_some());
+    app.handle_event(AppEvent::JobFinished(first, JobStatus::Cancelled));
+    assert!(app.retry().is_none());
+
+    app.handle_event(AppEvent::Retrying(second, notice));
+    app.handle_event(AppEvent::JobFinished(
+        second,
+        JobStatus::Failed(String::from("timeout")),
+    ));
+    assert!(app.retry().is_none());
+}
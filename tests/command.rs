@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use churro_cli::app::{
+    actions::Action,
+    command::{completions, ArgCommand, Command, CommandError, CommandLine},
+};
+use log::LevelFilter;
+
+#[test]
+fn parses_commands_and_arguments() {
+    let cases = [
+        ("sleep", Command::Action(Action::Sleep, None)),
+        ("sleep 3", Command::Sleep(Duration::from_secs(3))),
+        (":delay 7", Command::Delay(7)),
+        ("  login ", Command::Action(Action::Login, None)),
+        ("log-level warn", Command::LogLevel(LevelFilter::Warn)),
+        ("log-level DEBUG", Command::LogLevel(LevelFilter::Debug)),
+        ("fetch-item abc", Command::FetchItem(String::from("abc"))),
+        (
+            "increment-delay 4",
+            Command::Action(Action::IncrementDelay, Some(4)),
+        ),
+        // Asked for in a modal
+        ("delay", Command::Ask(ArgCommand::Delay)),
+        ("log-level", Command::Ask(ArgCommand::LogLevel)),
+        ("fetch-item", Command::Ask(ArgCommand::FetchItem)),
+    ];
+    for (line, command) in cases {
+        assert_eq!(line.parse::<Command>(), Ok(command), "{}", line);
+    }
+}
+
+#[test]
+fn reports_what_is_wrong() {
+    let cases = [
+        ("", "Not a command: "),
+        ("bogus", "Not a command: bogus"),
+        ("delay 1 2", "Usage: :delay <seconds>"),
+        ("sleep 1 2", "Usage: :sleep [seconds]"),
+        ("login now", "Usage: :login "),
+        (
+            "delay 0",
+            "Invalid argument `0`, expected seconds between 1 and 10",
+        ),
+        (
+            "log-level loud",
+            "Invalid argument `loud`, expected off, error, warn, info, debug, trace",
+        ),
+        (
+            "increment-delay x",
+            "Invalid argument `x`, expected a count",
+        ),
+    ];
+    for (line, error) in cases {
+        let err: CommandError = line.parse::<Command>().unwrap_err();
+        assert_eq!(err.to_string(), error, "{}", line);
+    }
+}
+
+#[test]
+fn completes_names_then_arguments() {
+    assert_eq!(completions("de", &[]), ["decrement-delay", "delay"]);
+    assert_eq!(completions("log-level w", &[]), ["log-level warn"]);
+    assert_eq!(completions("delay ", &[]).len(), 10);
+    assert_eq!(
+        completions("fetch-item a", &[String::from("ab"), String::from("b")]),
+        ["fetch-item ab"]
+    );
+    assert!(completions("delay 3 ", &[]).is_empty());
+    assert!(completions("quit ", &[]).is_empty());
+}
+
+#[test]
+fn tab_cycles_through_completions() {
+    let mut line = CommandLine::default();
+    line.open();
+    line.push_str("de");
+    line.complete(false, &[]);
+    assert_eq!(line.input(), "decrement-delay");
+    line.complete(false, &[]);
+    assert_eq!(line.input(), "delay");
+    line.complete(true, &[]);
+    assert_eq!(line.input(), "decrement-delay");
+
+    // A single candidate is final
+    let mut line = CommandLine::default();
+    line.open();
+    line.push_str("log-level e");
+    line.complete(false, &[]);
+    assert_eq!(line.input(), "log-level error");
+    assert!(line.completions().is_none());
+}
+
+#[test]
+fn goes_through_history() {
+    let mut line = CommandLine::default();
+    for command in ["delay 2", "login", "login"] {
+        line.open();
+        line.push_str(command);
+        line.submit().unwrap();
+        line.close();
+    }
+    assert_eq!(line.history(), ["delay 2", "login"]);
+
+    line.open();
+    line.push_str("sl");
+    line.history_previous();
+    assert_eq!(line.input(), "login");
+    line.history_previous();
+    assert_eq!(line.input(), "delay 2");
+    line.history_previous();
+    assert_eq!(line.input(), "delay 2");
+    line.history_next();
+    assert_eq!(line.input(), "login");
+    line.history_next();
+    assert_eq!(line.input(), "sl");
+}
@@ -0,0 +1,107 @@
+use std::sync::Once;
+
+use churro_cli::{
+    app::{
+        actions::{Action, Actions},
+        keymap::Keymap,
+        screens::Items,
+        App,
+    },
+    inputs::key::{Key, KeyCode},
+};
+use tokio::sync::mpsc;
+
+/// Keymaps with conflicts are refused when loaded, so bind them by hand
+fn install_conflicting_keymap() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let mut keymap = Keymap::default();
+        keymap.bind(Action::Sleep, vec!["g g".parse().unwrap()]);
+        keymap.bind(Action::Login, vec!["g".parse().unwrap()]);
+        keymap.bind(Action::CancelJob, vec!["q".parse().unwrap()]);
+        keymap.bind(Action::FetchItems, vec!["j".parse().unwrap()]);
+        keymap.install();
+    });
+}
+
+#[test]
+fn conflict_error_lists_every_conflict() {
+    install_conflicting_keymap();
+    let err = Actions::try_from(vec![
+        Action::Sleep,
+        Action::Login,
+        Action::Quit,
+        Action::CancelJob,
+    ])
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Conflicting key bindings:\n  \
+         - <g> <g> (Sleep) is shadowed by <g> (Login)\n  \
+         - <q> is bound to several actions: Quit, Cancel Job"
+    );
+    assert_eq!(
+        err.actions(),
+        vec![
+            Action::Sleep,
+            Action::Login,
+            Action::Quit,
+            Action::CancelJob
+        ]
+    );
+    assert!(Actions::try_from(vec![Action::Sleep, Action::Quit]).is_ok());
+}
+
+#[tokio::test]
+async fn app_drops_conflicting_actions() {
+    install_conflicting_keymap();
+    let (io_tx, _io_rx) = mpsc::channel(100);
+    let mut app = App::new(io_tx);
+    app.initialized();
+
+    let actions = app.actions().actions();
+    for action in [
+        Action::Quit,
+        Action::Sleep,
+        Action::Login,
+        Action::CancelJob,
+    ] {
+        assert!(!actions.contains(&action), "{} kept", action);
+    }
+    assert!(actions.contains(&Action::FetchItems));
+    assert!(actions.contains(&Action::Suspend));
+
+    // One report per screen, shown until dismissed
+    let diagnostics = app.diagnostics().to_vec();
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics
+        .iter()
+        .all(|problem| problem.starts_with("Conflicting key bindings:")));
+    assert!(diagnostics
+        .iter()
+        .any(|problem| problem.contains("<g> <g> (Sleep) is shadowed by <g> (Login)")));
+    app.do_action(Key::char('x')).await;
+    assert_eq!(app.diagnostics(), diagnostics);
+    app.do_action(Key::plain(KeyCode::Enter)).await;
+    assert!(app.diagnostics().is_empty());
+}
+
+#[tokio::test]
+async fn keys_taken_by_the_screen_are_conflicts() {
+    install_conflicting_keymap();
+    let (io_tx, _io_rx) = mpsc::channel(100);
+    let mut app = App::new(io_tx);
+    app.initialized();
+    app.do_action(Key::plain(KeyCode::Enter)).await;
+    assert!(app.actions().actions().contains(&Action::FetchItems));
+
+    // The items table moves down with j, before the keymap
+    app.push_screen(Box::new(Items::new()));
+    assert!(!app.actions().actions().contains(&Action::FetchItems));
+    assert_eq!(
+        app.diagnostics(),
+        ["Conflicting key bindings:\n  \
+          - <q> is bound to several actions: Quit, Cancel Job\n  \
+          - <j> (Fetch Items) is taken by the Items screen"]
+    );
+}
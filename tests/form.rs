@@ -0,0 +1,143 @@
+use churro_cli::{
+    app::form::{self, Field, FieldKind, Form, FormKey, FormValues, Value},
+    inputs::key::{Key, KeyCode, Modifiers},
+    io::IoEvent,
+};
+
+fn submit(values: &FormValues) -> IoEvent {
+    IoEvent::FetchItem(values.text("name").unwrap_or_default().to_string())
+}
+
+fn form() -> Form {
+    Form::new(submit)
+        .field(Field::text("name", "Name", "").validate_with(form::required))
+        .field(Field::number("count", "Count", 3, 1, 10))
+        .field(Field::toggle("verbose", "Verbose", false))
+        .field(Field::select(
+            "color",
+            "Color",
+            vec![String::from("red"), String::from("green")],
+            "green",
+        ))
+}
+
+fn press(form: &mut Form, keys: &[Key]) -> FormKey {
+    let mut result = FormKey::Ignored;
+    for key in keys {
+        result = form.on_key(*key);
+    }
+    result
+}
+
+fn plain(code: KeyCode) -> Key {
+    Key::plain(code)
+}
+
+fn chars(s: &str) -> Vec<Key> {
+    s.chars().map(Key::char).collect()
+}
+
+#[test]
+fn tab_moves_the_focus_around() {
+    let mut form = form();
+    press(&mut form, &[plain(KeyCode::Tab), plain(KeyCode::Down)]);
+    assert_eq!(form.focused(), 2);
+    press(&mut form, &[Key::new(KeyCode::Tab, Modifiers::SHIFT)]);
+    assert_eq!(form.focused(), 1);
+    press(&mut form, &[plain(KeyCode::Up), plain(KeyCode::Up)]);
+    assert_eq!(form.focused(), 3);
+    press(&mut form, &[plain(KeyCode::Tab)]);
+    assert_eq!(form.focused(), 0);
+}
+
+#[test]
+fn leaving_a_field_validates_it() {
+    let mut form = form();
+    press(&mut form, &[plain(KeyCode::Tab)]);
+    assert_eq!(form.fields()[0].error(), Some("Required"));
+
+    // Only digits go in numbers, the rest is swallowed
+    press(&mut form, &chars("a5q"));
+    assert_eq!(form.input().map(|input| input.value()), Some("35"));
+    press(&mut form, &[plain(KeyCode::Tab)]);
+    assert_eq!(
+        form.fields()[1].error(),
+        Some("Must be a number between 1 and 10")
+    );
+
+    // Editing clears the error until it's validated again
+    press(&mut form, &[Key::new(KeyCode::Tab, Modifiers::SHIFT)]);
+    assert!(form.fields()[1].error().is_some());
+    form.edit(|input| input.backspace());
+    assert_eq!(form.fields()[1].error(), None);
+}
+
+#[test]
+fn submits_valid_forms_only() {
+    let mut form = form();
+    assert!(matches!(
+        press(&mut form, &[plain(KeyCode::Tab), plain(KeyCode::Enter)]),
+        FormKey::Handled
+    ));
+    // The first invalid field gets the focus
+    assert_eq!(form.focused(), 0);
+
+    let mut keys = chars("churro");
+    keys.push(plain(KeyCode::Enter));
+    match press(&mut form, &keys) {
+        FormKey::Submit(IoEvent::FetchItem(name)) => assert_eq!(name, "churro"),
+        other => panic!("not submitted: {:?}", other),
+    }
+}
+
+#[test]
+fn toggles_and_selects_change_with_arrows() {
+    let mut form = form();
+    press(&mut form, &[plain(KeyCode::Tab), plain(KeyCode::Tab)]);
+    press(&mut form, &[Key::char(' ')]);
+    assert!(matches!(form.fields()[2].kind(), FieldKind::Toggle(true)));
+
+    // Wrapping around
+    press(&mut form, &[plain(KeyCode::Tab), plain(KeyCode::Right)]);
+    assert!(matches!(
+        form.fields()[3].kind(),
+        FieldKind::Select { selected: 0, .. }
+    ));
+    // Not for the form, the keymap can have it
+    assert!(matches!(
+        press(&mut form, &[Key::char('q')]),
+        FormKey::Ignored
+    ));
+    assert_eq!(form.input(), None);
+}
+
+#[test]
+fn values_by_name() {
+    let mut form = form();
+    form.edit(|input| input.paste("churro"));
+    press(&mut form, &[plain(KeyCode::Down), plain(KeyCode::Down)]);
+    press(&mut form, &[plain(KeyCode::Right), plain(KeyCode::Down)]);
+    press(&mut form, &[plain(KeyCode::Left), plain(KeyCode::Left)]);
+
+    let values = form.submit().unwrap();
+    assert_eq!(values.text("name"), Some("churro"));
+    assert_eq!(values.number("count"), Some(3));
+    assert_eq!(values.toggle("verbose"), Some(true));
+    assert_eq!(
+        values.get("color"),
+        Some(&Value::Select(String::from("green")))
+    );
+    assert_eq!(values.get("nope"), None);
+}
+
+#[test]
+fn validates_urls() {
+    let check = |url: &str| form::http_url(&Value::Text(url.to_string()));
+    assert_eq!(check(""), Ok(()));
+    assert_eq!(check("https://api.example.com/v1"), Ok(()));
+    assert!(check("example.com").is_err());
+    assert_eq!(
+        check("ftp://example.com"),
+        Err(String::from("Expected http or https, not ftp"))
+    );
+}
@@ -0,0 +1,130 @@
+use std::{collections::HashMap, time::Duration};
+
+use churro_cli::{
+    app::{jobs::JobStatus, AppEvent},
+    io::{self, handler::IoAsyncHandler, IoEvent, IoJob, JobId, DEFAULT_CONCURRENCY},
+};
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver},
+    time::timeout,
+};
+
+/// Handler running in the background, without API nor OAuth configured
+fn start_handler(max_concurrency: usize) -> (mpsc::Sender<IoJob>, UnboundedReceiver<AppEvent>) {
+    let (io_tx, io_rx) = mpsc::channel(100);
+    let (app_tx, app_rx) = mpsc::unbounded_channel();
+    tokio::spawn(IoAsyncHandler::new(app_tx).run(io_rx, max_concurrency));
+    (io_tx, app_rx)
+}
+
+async fn sleep_job(io_tx: &mpsc::Sender<IoJob>, id: u64, millis: u64) {
+    let event = IoEvent::Sleep(Duration::from_millis(millis));
+    io_tx
+        .send(IoJob {
+            id: JobId(id),
+            event,
+        })
+        .await
+        .unwrap();
+}
+
+async fn next_event(app_rx: &mut UnboundedReceiver<AppEvent>) -> AppEvent {
+    timeout(Duration::from_secs(5), app_rx.recv())
+        .await
+        .expect("no app event in time")
+        .expect("handler is gone")
+}
+
+#[tokio::test]
+async fn runs_no_more_jobs_than_the_limit() {
+    let (io_tx, mut app_rx) = start_handler(2);
+    for id in 0..6 {
+        sleep_job(&io_tx, id, 50).await;
+    }
+
+    let mut spawned = 0;
+    let mut running = 0;
+    let mut most_running = 0;
+    let mut finished = HashMap::new();
+    while finished.len() < 6 {
+        match next_event(&mut app_rx).await {
+            AppEvent::JobSpawned(..) => spawned += 1,
+            AppEvent::JobStarted(_) => {
+                running += 1;
+                most_running = most_running.max(running);
+            }
+            // Sent while the job still holds its permit
+            AppEvent::Slept => running -= 1,
+            AppEvent::JobFinished(id, status) => {
+                finished.insert(id, status);
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(spawned, 6);
+    assert_eq!(most_running, 2);
+    assert!(finished
+        .values()
+        .all(|status| *status == JobStatus::Succeeded));
+}
+
+#[tokio::test]
+async fn reports_cancelled_jobs() {
+    let (io_tx, mut app_rx) = start_handler(1);
+    sleep_job(&io_tx, 1, 10_000).await;
+    // Waiting for a permit, cancelled before it starts
+    sleep_job(&io_tx, 2, 10_000).await;
+    sleep_job(&io_tx, 3, 10).await;
+
+    let mut started = vec![];
+    let mut finished = vec![];
+    while finished.len() < 3 {
+        match next_event(&mut app_rx).await {
+            AppEvent::JobSpawned(JobId(3), _) => {}
+            AppEvent::JobSpawned(_, abort) => abort.abort(),
+            AppEvent::JobStarted(id) => started.push(id),
+            AppEvent::JobFinished(id, status) => finished.push((id, status)),
+            _ => {}
+        }
+    }
+    assert!(!started.contains(&JobId(2)));
+    finished.sort_by_key(|(id, _)| *id);
+    assert_eq!(
+        finished,
+        [
+            (JobId(1), JobStatus::Cancelled),
+            (JobId(2), JobStatus::Cancelled),
+            (JobId(3), JobStatus::Succeeded),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn reports_failed_jobs() {
+    let (io_tx, mut app_rx) = start_handler(1);
+    let event = IoEvent::FetchItem(String::from("1"));
+    io_tx
+        .send(IoJob {
+            id: JobId(1),
+            event,
+        })
+        .await
+        .unwrap();
+    loop {
+        if let AppEvent::JobFinished(id, status) = next_event(&mut app_rx).await {
+            assert_eq!(id, JobId(1));
+            // No API configured
+            assert!(matches!(status, JobStatus::Failed(_)), "{}", status);
+            break;
+        }
+    }
+}
+
+#[test]
+fn concurrency_defaults_unless_a_positive_number() {
+    assert_eq!(io::concurrency(Some("8")), 8);
+    assert_eq!(io::concurrency(Some(" 2 ")), 2);
+    for value in [None, Some("0"), Some("-1"), Some("many"), Some("")] {
+        assert_eq!(io::concurrency(value), DEFAULT_CONCURRENCY, "{:?}", value);
+    }
+}
@@ -0,0 +1,150 @@
+use churro_cli::{
+    app::input::{InputView, TextInput},
+    inputs::key::{Key, KeyCode, Modifiers},
+};
+
+fn input(value: &str) -> TextInput {
+    let mut input = TextInput::new();
+    input.set_value(value);
+    input
+}
+
+fn press(input: &mut TextInput, keys: &[Key]) {
+    for key in keys {
+        input.on_key(*key);
+    }
+}
+
+fn plain(code: KeyCode) -> Key {
+    Key::plain(code)
+}
+
+fn ctrl(code: KeyCode) -> Key {
+    Key::new(code, Modifiers::CTRL)
+}
+
+/// The value with a `|` at the cursor
+fn shown(input: &TextInput) -> String {
+    let mut value = input.value().chars().collect::<Vec<_>>();
+    value.insert(input.cursor(), '|');
+    value.into_iter().collect()
+}
+
+#[test]
+fn edits_at_the_cursor() {
+    let mut input = input("helo");
+    press(&mut input, &[plain(KeyCode::Left), Key::char('l')]);
+    assert_eq!(shown(&input), "hell|o");
+    press(&mut input, &[plain(KeyCode::Home), plain(KeyCode::Delete)]);
+    assert_eq!(shown(&input), "|ello");
+    press(&mut input, &[Key::ctrl('e'), plain(KeyCode::Backspace)]);
+    assert_eq!(shown(&input), "ell|");
+    press(
+        &mut input,
+        &[Key::ctrl('a'), plain(KeyCode::Right), Key::ctrl('k')],
+    );
+    assert_eq!(shown(&input), "e|");
+}
+
+#[test]
+fn moves_and_deletes_by_word() {
+    let mut input = input("fetch-item abc  12");
+    press(&mut input, &[ctrl(KeyCode::Left)]);
+    assert_eq!(shown(&input), "fetch-item abc  |12");
+    press(&mut input, &[Key::alt('b'), Key::alt('b')]);
+    assert_eq!(shown(&input), "fetch-|item abc  12");
+    press(&mut input, &[ctrl(KeyCode::Right)]);
+    assert_eq!(shown(&input), "fetch-item| abc  12");
+    press(&mut input, &[Key::alt('f')]);
+    assert_eq!(shown(&input), "fetch-item abc|  12");
+
+    // Ctrl+W goes back to whitespace, like a shell
+    press(&mut input, &[Key::ctrl('w')]);
+    assert_eq!(shown(&input), "fetch-item |  12");
+    press(&mut input, &[Key::ctrl('w')]);
+    assert_eq!(shown(&input), "|  12");
+    press(&mut input, &[plain(KeyCode::End), Key::ctrl('u')]);
+    assert_eq!(shown(&input), "|");
+}
+
+#[test]
+fn handles_wide_chars() {
+    let mut input = input("añ日本");
+    press(
+        &mut input,
+        &[plain(KeyCode::Left), plain(KeyCode::Backspace)],
+    );
+    assert_eq!(shown(&input), "añ|本");
+    press(&mut input, &[Key::alt('b')]);
+    assert_eq!(shown(&input), "|añ本");
+}
+
+#[test]
+fn leaves_other_keys_to_its_owner() {
+    let mut input = input("");
+    for key in [
+        plain(KeyCode::Enter),
+        plain(KeyCode::Esc),
+        plain(KeyCode::Tab),
+        plain(KeyCode::Up),
+        plain(KeyCode::Backspace),
+        Key::ctrl('c'),
+    ] {
+        assert!(!input.on_key(key), "{}", key);
+    }
+    assert!(input.on_key(Key::char('A')));
+}
+
+#[test]
+fn views_scroll_to_the_cursor() {
+    let view = |before: &str, cursor: &str, after: &str| InputView {
+        before: before.to_string(),
+        cursor: cursor.to_string(),
+        after: after.to_string(),
+    };
+    let mut input = input("abcdef");
+    assert_eq!(input.view(10), view("abcdef", " ", ""));
+    assert_eq!(input.view(4), view("def", " ", ""));
+    press(&mut input, &[plain(KeyCode::Home)]);
+    assert_eq!(input.view(4), view("", "a", "bcd"));
+
+    // Wide chars take two columns and are never cut
+    let mut input = self::input("日本語");
+    assert_eq!(input.view(4), view("語", " ", ""));
+    press(&mut input, &[plain(KeyCode::Home)]);
+    assert_eq!(input.view(5), view("", "日", "本"));
+}
+
+#[test]
+fn masks_secrets() {
+    let mut input = TextInput::masked();
+    input.paste("my secret\nnext line");
+    assert_eq!(input.value(), "my secret");
+    assert_eq!(input.view(20).before, "*********");
+    // Word movement doesn't give the words away
+    press(&mut input, &[Key::alt('b')]);
+    assert_eq!(input.cursor(), 0);
+    input.commit();
+    assert!(input.history().is_empty());
+}
+
+#[test]
+fn keeps_its_own_history() {
+    let mut input = TextInput::new();
+    for line in ["one", "two", "two", "  "] {
+        input.set_value(line);
+        input.commit();
+    }
+    assert_eq!(input.history(), ["one", "two"]);
+
+    input.set_value("draft");
+    input.history_previous();
+    assert_eq!(input.value(), "two");
+    input.history_previous();
+    input.history_previous();
+    assert_eq!(input.value(), "one");
+    input.history_next();
+    assert_eq!(input.value(), "two");
+    input.history_next();
+    assert_eq!(input.value(), "draft");
+}
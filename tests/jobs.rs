@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use churro_cli::{
+    app::jobs::{JobStatus, Jobs, KEEP_FINISHED},
+    io::{IoEvent, JobId},
+};
+
+fn sleep() -> IoEvent {
+    IoEvent::Sleep(Duration::from_secs(1))
+}
+
+fn status(jobs: &Jobs, id: u64) -> Option<&JobStatus> {
+    jobs.get(JobId(id)).map(|job| job.status())
+}
+
+#[test]
+fn tracks_jobs_until_finished() {
+    let mut jobs = Jobs::default();
+    assert!(jobs.is_idle());
+    jobs.add(JobId(1), sleep());
+    jobs.add(JobId(2), IoEvent::FetchItems);
+    assert_eq!(status(&jobs, 1), Some(&JobStatus::Pending));
+    jobs.start(JobId(1));
+    assert_eq!(status(&jobs, 1), Some(&JobStatus::Running));
+
+    let job = jobs.finish(JobId(1), JobStatus::Succeeded).unwrap();
+    assert_eq!(job.status(), &JobStatus::Succeeded);
+    let ids = jobs.in_flight().map(|job| job.id()).collect::<Vec<_>>();
+    assert_eq!(ids, [JobId(2)]);
+
+    // Only finished once, the first outcome stays
+    assert!(jobs
+        .finish(JobId(1), JobStatus::Failed(String::from("late")))
+        .is_none());
+    assert_eq!(status(&jobs, 1), Some(&JobStatus::Succeeded));
+    assert!(jobs.finish(JobId(3), JobStatus::Succeeded).is_none());
+
+    jobs.finish(JobId(2), JobStatus::Cancelled);
+    assert!(jobs.is_idle());
+}
+
+#[tokio::test]
+async fn cancels_jobs_with_an_abort_handle() {
+    let mut jobs = Jobs::default();
+    jobs.add(JobId(1), sleep());
+    // Not spawned yet, nothing to abort
+    assert!(!jobs.cancel(JobId(1)));
+    assert!(!jobs.cancel(JobId(2)));
+
+    let task = tokio::spawn(tokio::time::sleep(Duration::from_secs(10)));
+    jobs.set_abort_handle(JobId(1), task.abort_handle());
+    assert!(jobs.cancel(JobId(1)));
+    assert!(task.await.unwrap_err().is_cancelled());
+    // Still in flight until the handler reports it
+    assert_eq!(status(&jobs, 1), Some(&JobStatus::Pending));
+
+    jobs.finish(JobId(1), JobStatus::Cancelled);
+    assert!(!jobs.cancel(JobId(1)));
+}
+
+#[test]
+fn keeps_the_latest_finished_jobs() {
+    let mut jobs = Jobs::default();
+    let total = KEEP_FINISHED as u64 + 5;
+    jobs.add(JobId(0), sleep());
+    for id in 1..=total {
+        jobs.add(JobId(id), sleep());
+        jobs.finish(JobId(id), JobStatus::Succeeded);
+    }
+    // In flight jobs are never dropped
+    assert_eq!(status(&jobs, 0), Some(&JobStatus::Pending));
+    assert!(jobs.get(JobId(5)).is_none());
+    assert!(jobs.get(JobId(6)).is_some());
+    assert!(jobs.get(JobId(total)).is_some());
+}
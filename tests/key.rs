@@ -0,0 +1,200 @@
+use churro_cli::inputs::key::{EmptyKeySequence, Key, KeyCode, KeySequence, Modifiers};
+
+const NAMED: [KeyCode; 15] = [
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Esc,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Ins,
+    KeyCode::Delete,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Unknown,
+];
+
+/// Every combination of Ctrl, Alt, Super, Hyper and Meta
+fn modifier_sets() -> Vec<Modifiers> {
+    let single = [
+        Modifiers::CTRL,
+        Modifiers::ALT,
+        Modifiers::SUPER,
+        Modifiers::HYPER,
+        Modifiers::META,
+    ];
+    (0..1 << single.len())
+        .map(|bits| {
+            single
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| bits & (1 << i) != 0)
+                .fold(Modifiers::NONE, |all, (_, modifier)| all | *modifier)
+        })
+        .collect()
+}
+
+/// Printable ASCII plus the tricky ones for the `<..+..>` syntax
+fn chars() -> impl Iterator<Item = char> {
+    (' '..='~').chain(['é', 'ß', '€'])
+}
+
+fn all_keys() -> Vec<Key> {
+    let codes: Vec<KeyCode> = NAMED
+        .into_iter()
+        .chain((0..=24).map(KeyCode::F))
+        .chain(chars().map(KeyCode::Char))
+        .collect();
+    let mut keys = Vec::new();
+    for modifiers in modifier_sets() {
+        for code in &codes {
+            keys.push(Key::new(*code, modifiers));
+            if !matches!(code, KeyCode::Char(_)) {
+                keys.push(Key::new(*code, modifiers | Modifiers::SHIFT));
+            }
+        }
+    }
+    keys
+}
+
+#[test]
+fn display_round_trips() {
+    for key in all_keys() {
+        assert_eq!(key.to_string().parse::<Key>(), Ok(key), "{}", key);
+    }
+}
+
+#[test]
+fn serde_round_trips() {
+    for key in all_keys() {
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, serde_json::to_string(&key.to_string()).unwrap());
+        assert_eq!(serde_json::from_str::<Key>(&json).unwrap(), key, "{}", json);
+    }
+}
+
+#[test]
+fn displays_modifiers_in_order() {
+    let cases = [
+        (Key::ctrl('c'), "<Ctrl+c>"),
+        (
+            Key::new(KeyCode::Char('x'), Modifiers::ALT | Modifiers::CTRL),
+            "<Ctrl+Alt+x>",
+        ),
+        (Key::new(KeyCode::Tab, Modifiers::SHIFT), "<Shift+Tab>"),
+        (Key::new(KeyCode::Char('a'), Modifiers::SHIFT), "<A>"),
+        (
+            Key::new(KeyCode::Char('a'), Modifiers::CTRL | Modifiers::SHIFT),
+            "<Ctrl+A>",
+        ),
+        (
+            Key::new(KeyCode::Left, Modifiers::META | Modifiers::SUPER),
+            "<Super+Meta+Left>",
+        ),
+        (Key::from_f(5), "<F5>"),
+        (Key::alt(' '), "<Alt+Space>"),
+    ];
+    for (key, s) in cases {
+        assert_eq!(key.to_string(), s);
+    }
+}
+
+#[test]
+fn parses_lenient_forms() {
+    let cases = [
+        ("q", Key::char('q')),
+        ("<q>", Key::char('q')),
+        ("+", Key::char('+')),
+        ("<Space>", Key::char(' ')),
+        ("<space>", Key::char(' ')),
+        ("<ctrl+c>", Key::ctrl('c')),
+        ("<CTRL+C>", Key::ctrl('C')),
+        ("<Ctrl+Space>", Key::ctrl(' ')),
+        ("<alt+space>", Key::alt(' ')),
+        ("<Ctrl++>", Key::ctrl('+')),
+        ("<Shift+a>", Key::char('A')),
+        ("<ctrl+shift+a>", Key::ctrl('A')),
+        (
+            "<Alt+Ctrl+x>",
+            Key::new(KeyCode::Char('x'), Modifiers::CTRL | Modifiers::ALT),
+        ),
+        ("<shift+tab>", Key::new(KeyCode::Tab, Modifiers::SHIFT)),
+        ("<Ctrl+Enter>", Key::new(KeyCode::Enter, Modifiers::CTRL)),
+        ("<Hyper+q>", Key::new(KeyCode::Char('q'), Modifiers::HYPER)),
+        ("<f5>", Key::from_f(5)),
+        ("<F12>", Key::from_f(12)),
+        ("<F13>", Key::from_f(13)),
+        ("Enter", Key::plain(KeyCode::Enter)),
+        ("<pagedown>", Key::plain(KeyCode::PageDown)),
+    ];
+    for (s, key) in cases {
+        assert_eq!(s.parse::<Key>(), Ok(key), "{}", s);
+    }
+}
+
+#[test]
+fn rejects_invalid_keys() {
+    for s in [
+        "",
+        "<>",
+        "qq",
+        "<F256>",
+        "<Fx>",
+        "<Foo+q>",
+        "<Ctrl+>",
+        "<Ctrl+Ctrl>",
+        "<Shift+1>",
+        "<Shift+A>",
+        "<Bogus>",
+    ] {
+        assert!(s.parse::<Key>().is_err(), "{}", s);
+    }
+    assert!(serde_json::from_str::<Key>(r#""<Bogus>""#).is_err());
+}
+
+#[test]
+fn existing_bindings_still_match() {
+    assert!(Key::ctrl('c').is_exit());
+    assert!(Key::char('q').is_exit());
+    assert!(Key::plain(KeyCode::Esc).is_exit());
+    assert!(!Key::new(KeyCode::Char('c'), Modifiers::CTRL | Modifiers::ALT).is_exit());
+    assert_eq!("<Ctrl+z>".parse::<Key>(), Ok(Key::ctrl('z')));
+}
+
+#[test]
+fn parses_sequences() {
+    let cases = [
+        ("g g", vec![Key::char('g'), Key::char('g')]),
+        ("<Ctrl+x> <Ctrl+s>", vec![Key::ctrl('x'), Key::ctrl('s')]),
+        ("  <Space>  q ", vec![Key::char(' '), Key::char('q')]),
+        (" ", vec![Key::char(' ')]),
+        ("<F5>", vec![Key::from_f(5)]),
+    ];
+    for (s, keys) in cases {
+        let sequence = s.parse::<KeySequence>();
+        assert_eq!(sequence, Ok(KeySequence::try_from(keys).unwrap()), "{}", s);
+        let sequence = sequence.unwrap();
+        assert_eq!(sequence.to_string().parse(), Ok(sequence.clone()));
+        let json = serde_json::to_string(&sequence).unwrap();
+        assert_eq!(
+            serde_json::from_str::<KeySequence>(&json).unwrap(),
+            sequence
+        );
+    }
+    for s in ["", "g gg", "<Ctrl+x> <Bogus>"] {
+        assert!(s.parse::<KeySequence>().is_err(), "{}", s);
+    }
+}
+
+#[test]
+fn sequences_are_never_empty() {
+    assert_eq!(KeySequence::try_from(vec![]), Err(EmptyKeySequence));
+    assert_eq!(
+        KeySequence::from(Key::char('g')).then(Key::char('i')),
+        "g i".parse().unwrap()
+    );
+}
@@ -0,0 +1,114 @@
+use std::{fs, path::PathBuf};
+
+use churro_cli::app::{
+    actions::Action,
+    keymap::{Keymap, KeymapError},
+};
+
+/// Keymap file with that content, in its own dir
+fn keymap_file(name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("churro-cli-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn load(name: &str, content: &str) -> Result<Keymap, KeymapError> {
+    let path = keymap_file(name, content);
+    let keymap = Keymap::from_file(&path);
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    keymap
+}
+
+fn keys(keymap: &Keymap, action: Action) -> Vec<String> {
+    keymap
+        .keys(action)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn merges_over_the_defaults() {
+    let keymap = load(
+        "merge.toml",
+        r#"
+        [keys]
+        quit = ["x", "<Ctrl+c>"]
+        fetch-items = "<F5>"
+        login = "g l"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(keys(&keymap, Action::Quit), ["<x>", "<Ctrl+c>"]);
+    assert_eq!(keys(&keymap, Action::FetchItems), ["<F5>"]);
+    assert_eq!(keys(&keymap, Action::Login), ["<g> <l>"]);
+    // Not in the file, still bound
+    assert_eq!(
+        keymap.keys(Action::Sleep),
+        Keymap::default().keys(Action::Sleep)
+    );
+
+    let keymap = load("merge.json", r#"{ "keys": { "quit": "x" } }"#).unwrap();
+    assert_eq!(keys(&keymap, Action::Quit), ["<x>"]);
+    let keymap = load("empty.toml", "").unwrap();
+    assert_eq!(
+        keys(&keymap, Action::Quit),
+        keys(&Keymap::default(), Action::Quit)
+    );
+}
+
+#[test]
+fn lists_every_invalid_key() {
+    let err = load(
+        "invalid.toml",
+        r#"
+        [keys]
+        quit = ["x", "<Win+x>"]
+        sleep = "<Shift+1>"
+        "#,
+    )
+    .unwrap_err();
+    assert_eq!(err.problems.len(), 2, "{}", err);
+    assert!(err
+        .problems
+        .contains(&String::from("Quit: invalid key `<Win+x>`")));
+    assert!(err
+        .problems
+        .contains(&String::from("Sleep: invalid key `<Shift+1>`")));
+    assert!(err.to_string().starts_with("Invalid keymap "));
+}
+
+#[test]
+fn reports_conflicts() {
+    let err = load(
+        "conflicts.toml",
+        r#"
+        [keys]
+        sleep = "q"
+        login = "g"
+        "#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.problems,
+        [
+            "<g> <i> (Show Items) is shadowed by <g> (Login)",
+            "<g> <s> (Settings) is shadowed by <g> (Login)",
+            "<q> is bound to several actions: Quit, Sleep",
+        ],
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn unreadable_files_are_errors() {
+    let err = load("unknown.toml", "[keys]\nfly = \"x\"\n").unwrap_err();
+    assert_eq!(err.problems.len(), 1);
+    assert!(err.problems[0].contains("fly"), "{}", err);
+
+    let missing = std::env::temp_dir().join("churro-cli-missing-keymap.toml");
+    assert!(Keymap::from_file(&missing).is_err());
+}
@@ -0,0 +1,115 @@
+use churro_cli::app::{
+    actions::{Action, Actions},
+    table::TableView,
+    ui::{self, Areas, FULL_HEIGHT, FULL_WIDTH, MIN_HEIGHT, MIN_WIDTH},
+};
+use tui::layout::Rect;
+
+fn areas(width: u16, height: u16) -> Option<Areas> {
+    ui::areas(Rect::new(0, 0, width, height))
+}
+
+fn inside(area: Rect, size: Rect) -> bool {
+    area.x >= size.x
+        && area.y >= size.y
+        && area.right() <= size.right()
+        && area.bottom() <= size.bottom()
+}
+
+#[test]
+fn nothing_below_the_minimum() {
+    for (width, height) in [
+        (0, 0),
+        (1, 1),
+        (MIN_WIDTH - 1, MIN_HEIGHT),
+        (MIN_WIDTH, MIN_HEIGHT - 1),
+        (MIN_WIDTH - 1, FULL_HEIGHT),
+        (FULL_WIDTH, MIN_HEIGHT - 1),
+        (u16::MAX, 0),
+        (0, u16::MAX),
+    ] {
+        assert!(areas(width, height).is_none(), "{}x{}", width, height);
+    }
+}
+
+#[test]
+fn panes_collapse_below_full_size() {
+    for (width, height, help, logs) in [
+        (MIN_WIDTH, MIN_HEIGHT, false, false),
+        (FULL_WIDTH - 1, FULL_HEIGHT - 1, false, false),
+        (FULL_WIDTH, FULL_HEIGHT - 1, true, false),
+        (FULL_WIDTH - 1, FULL_HEIGHT, false, true),
+        (FULL_WIDTH, FULL_HEIGHT, true, true),
+        (200, 60, true, true),
+    ] {
+        let areas = areas(width, height).unwrap();
+        let size = format!("{}x{}", width, height);
+        assert_eq!(areas.help.is_some(), help, "{}", size);
+        assert_eq!(areas.logs.is_some(), logs, "{}", size);
+    }
+}
+
+/// Sizes at and around each threshold
+fn sizes(min: u16, full: u16) -> [u16; 9] {
+    [0, 1, min - 1, min, min + 1, full - 1, full, full + 1, 500]
+}
+
+#[test]
+fn panes_fit_and_never_overlap() {
+    for width in sizes(MIN_WIDTH, FULL_WIDTH) {
+        for height in sizes(MIN_HEIGHT, FULL_HEIGHT) {
+            let size = Rect::new(0, 0, width, height);
+            let Some(areas) = ui::areas(size) else {
+                continue;
+            };
+            let mut panes = vec![areas.title, areas.body, areas.duration];
+            panes.extend(areas.help);
+            panes.extend(areas.logs);
+            for (i, pane) in panes.iter().enumerate() {
+                assert!(inside(*pane, size), "{}x{}: {:?}", width, height, pane);
+                assert!(pane.area() > 0, "{}x{}: {:?}", width, height, pane);
+                for other in &panes[i + 1..] {
+                    assert!(!pane.intersects(*other), "{}x{}", width, height);
+                }
+            }
+            assert!(areas.body.height >= 4, "{}x{}", width, height);
+        }
+    }
+}
+
+#[test]
+fn help_rows_are_below_the_header() {
+    let actions = Actions::try_from(vec![Action::Quit, Action::Sleep]).unwrap();
+    let help = ui::help_table();
+    let area = Rect::new(40, 3, 32, 10);
+    let action_at = |row| ui::help_action_at(&help, &actions, area, 45, row);
+    // Border, header, q and Ctrl+c for Quit, then s
+    assert_eq!(action_at(3), None);
+    assert_eq!(action_at(4), None);
+    assert_eq!(action_at(5), Some(Action::Quit));
+    assert_eq!(action_at(6), Some(Action::Quit));
+    assert_eq!(action_at(7), Some(Action::Sleep));
+    assert_eq!(action_at(8), None);
+    assert_eq!(ui::help_action_at(&help, &actions, area, 40, 5), None);
+}
+
+#[test]
+fn help_scrolls_to_its_last_row() {
+    let actions = Actions::try_from(vec![Action::Quit, Action::Sleep, Action::Login]).unwrap();
+    let mut help = ui::help_table();
+    // Room for two of the four rows
+    let area = Rect::new(0, 0, 32, 5);
+    let action_at = |help: &TableView, row| ui::help_action_at(help, &actions, area, 5, row);
+    assert_eq!(action_at(&help, 2), Some(Action::Quit));
+
+    ui::scroll_help(&mut help, &actions, area, 1);
+    assert_eq!(action_at(&help, 2), Some(Action::Quit));
+    assert_eq!(action_at(&help, 3), Some(Action::Sleep));
+    ui::scroll_help(&mut help, &actions, area, 5);
+    assert_eq!(action_at(&help, 2), Some(Action::Sleep));
+    assert_eq!(action_at(&help, 3), Some(Action::Login));
+    ui::scroll_help(&mut help, &actions, area, -1);
+    ui::scroll_help(&mut help, &actions, area, -1);
+    assert_eq!(action_at(&help, 2), Some(Action::Quit));
+    assert_eq!(help.selected(), 0);
+}
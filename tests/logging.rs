@@ -0,0 +1,88 @@
+use std::{
+    fs,
+    sync::{Mutex, Once},
+    thread,
+};
+
+use churro_cli::{
+    logging::{self, RECENT_RECORDS},
+    terminal,
+};
+use log::{info, LevelFilter};
+
+/// The logger and its records are global, tests take turns
+static LOGGING: Mutex<()> = Mutex::new(());
+
+fn init() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| logging::init(LevelFilter::Debug).unwrap());
+}
+
+#[test]
+fn keeps_the_most_recent_records() {
+    let _turn = LOGGING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    init();
+    for n in 0..RECENT_RECORDS + 20 {
+        info!("record {}", n);
+    }
+
+    let recent = logging::recent();
+    assert_eq!(recent.len(), RECENT_RECORDS);
+    assert!(recent[0].ends_with(": record 20"), "{}", recent[0]);
+    assert!(recent[RECENT_RECORDS - 1].ends_with(&format!(": record {}", RECENT_RECORDS + 19)));
+}
+
+#[test]
+fn records_debug_whatever_level_is_shown() {
+    let _turn = LOGGING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    init();
+    logging::set_level(LevelFilter::Warn);
+    log::debug!("hidden but recorded");
+    logging::set_level(LevelFilter::Debug);
+
+    assert_eq!(logging::level(), LevelFilter::Debug);
+    assert!(logging::recent()
+        .last()
+        .is_some_and(|line| line.contains("DEBUG") && line.ends_with(": hidden but recorded")));
+}
+
+#[test]
+fn crash_reports_have_the_panic_and_recent_records() {
+    let _turn = LOGGING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    init();
+    let state = std::env::temp_dir().join(format!("churro-crash-{}", std::process::id()));
+    std::env::set_var("XDG_STATE_HOME", &state);
+    terminal::install_panic_hook();
+
+    info!("about to crash");
+    // Off the UI thread, only the report is written
+    let crashed = thread::Builder::new()
+        .name(String::from("io"))
+        .spawn(|| panic!("the job blew up"))
+        .unwrap()
+        .join();
+    assert!(crashed.is_err());
+
+    let reports = fs::read_dir(state.join("churro-cli"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(reports.len(), 1);
+    let report = fs::read_to_string(&reports[0]).unwrap();
+    fs::remove_dir_all(&state).unwrap();
+
+    assert!(report.starts_with(&format!(
+        "churro-cli {} crashed\nthread: io\n",
+        env!("CARGO_PKG_VERSION")
+    )));
+    assert!(report.contains("the job blew up"));
+    assert!(report.contains("\nBacktrace:\n"));
+    let logs = report.split("\nRecent logs:\n").nth(1).unwrap();
+    assert!(logs.lines().any(|line| line.ends_with(": about to crash")));
+}
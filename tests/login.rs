@@ -0,0 +1,107 @@
+use std::{collections::HashMap, convert::Infallible, net::SocketAddr, time::Duration};
+
+use churro_cli::auth::{login::login, OAuthConfig};
+use hyper::{
+    body,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+
+/// Stand-in authorization server
+///
+/// `/authorize` redirects straight back with a code, `/token` hands out tokens
+/// as long as the PKCE verifier is present.
+async fn provider(req: Request<Body>, tamper_state: bool) -> Result<Response<Body>, Infallible> {
+    match req.uri().path() {
+        "/authorize" => {
+            let query: HashMap<String, String> =
+                url::form_urlencoded::parse(req.uri().query().unwrap_or_default().as_bytes())
+                    .into_owned()
+                    .collect();
+            assert_eq!(query["response_type"], "code");
+            assert_eq!(query["code_challenge_method"], "S256");
+            let state = if tamper_state {
+                String::from("forged")
+            } else {
+                query["state"].clone()
+            };
+            let location = format!(
+                "{}?code=the-code&state={}",
+                query["redirect_uri"],
+                url::form_urlencoded::byte_serialize(state.as_bytes()).collect::<String>()
+            );
+            Ok(Response::builder()
+                .status(StatusCode::FOUND)
+                .header("location", location)
+                .body(Body::empty())
+                .unwrap())
+        }
+        "/token" => {
+            let form = body::to_bytes(req.into_body()).await.unwrap();
+            let form: HashMap<String, String> =
+                url::form_urlencoded::parse(&form).into_owned().collect();
+            assert_eq!(form["grant_type"], "authorization_code");
+            assert_eq!(form["code"], "the-code");
+            assert!(form.contains_key("code_verifier"));
+            Ok(Response::builder()
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"access_token":"access","token_type":"bearer","expires_in":3600,"refresh_token":"refresh"}"#,
+                ))
+                .unwrap())
+        }
+        _ => Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap()),
+    }
+}
+
+fn start_provider(tamper_state: bool) -> SocketAddr {
+    let make_svc = make_service_fn(move |_| async move {
+        Ok::<_, Infallible>(service_fn(move |req| provider(req, tamper_state)))
+    });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+fn config(provider: SocketAddr) -> OAuthConfig {
+    OAuthConfig {
+        client_id: String::from("churro"),
+        client_secret: None,
+        auth_url: format!("http://{}/authorize", provider),
+        token_url: format!("http://{}/token", provider),
+        scopes: vec![String::from("read")],
+        redirect_port: 0,
+        callback_timeout: Duration::from_secs(5),
+    }
+}
+
+/// Plays the browser: follow the provider redirects back to the callback
+fn browser(url: &url::Url) -> anyhow::Result<()> {
+    let url = url.clone();
+    tokio::spawn(async move { reqwest::get(url).await });
+    Ok(())
+}
+
+#[tokio::test]
+async fn login_exchanges_code_for_tokens() {
+    let provider = start_provider(false);
+
+    let tokens = login(&config(provider), browser).await.unwrap();
+
+    assert_eq!(tokens.access_token, "access");
+    assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+    assert!(tokens.expires_at.is_some());
+}
+
+#[tokio::test]
+async fn login_rejects_mismatched_state() {
+    let provider = start_provider(true);
+
+    let err = login(&config(provider), browser).await.unwrap_err();
+
+    assert!(err.to_string().contains("state"));
+}
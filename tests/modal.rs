@@ -0,0 +1,109 @@
+use churro_cli::{
+    app::{
+        input::TextInput,
+        modal::{Modal, ModalAnswer, ModalKind},
+    },
+    inputs::key::{Key, KeyCode},
+};
+
+fn plain(code: KeyCode) -> Key {
+    Key::plain(code)
+}
+
+#[test]
+fn confirm_defaults_to_no() {
+    let mut modal = Modal::confirm("Quit", "Sure?");
+    assert_eq!(
+        modal.on_key(plain(KeyCode::Enter)),
+        Some(ModalAnswer::Cancelled)
+    );
+
+    let mut modal = Modal::confirm("Quit", "Sure?");
+    assert_eq!(modal.on_key(plain(KeyCode::Tab)), None);
+    assert_eq!(modal.kind(), &ModalKind::Confirm { yes: true });
+    assert_eq!(
+        modal.on_key(plain(KeyCode::Enter)),
+        Some(ModalAnswer::Confirmed)
+    );
+
+    let mut modal = Modal::confirm("Quit", "Sure?");
+    assert_eq!(modal.on_key(Key::char('q')), None);
+    assert_eq!(modal.on_key(Key::char('y')), Some(ModalAnswer::Confirmed));
+    assert_eq!(modal.on_key(Key::char('n')), Some(ModalAnswer::Cancelled));
+    assert_eq!(modal.on_key(Key::ctrl('y')), None);
+}
+
+#[test]
+fn choice_moves_within_the_options() {
+    let options = vec![String::from("a"), String::from("b"), String::from("c")];
+    let mut modal = Modal::choice("Pick", "One", options);
+    modal.on_key(plain(KeyCode::Up));
+    assert_eq!(
+        modal.on_key(plain(KeyCode::Enter)),
+        Some(ModalAnswer::Chosen(0))
+    );
+    for _ in 0..5 {
+        modal.on_key(Key::char('j'));
+    }
+    assert_eq!(
+        modal.on_key(plain(KeyCode::Enter)),
+        Some(ModalAnswer::Chosen(2))
+    );
+    modal.on_key(Key::char('k'));
+    assert_eq!(
+        modal.on_key(plain(KeyCode::Enter)),
+        Some(ModalAnswer::Chosen(1))
+    );
+
+    // Digits pick directly, when there's such an option
+    assert_eq!(modal.on_key(Key::char('3')), Some(ModalAnswer::Chosen(2)));
+    assert_eq!(modal.on_key(Key::char('4')), None);
+    assert_eq!(modal.on_key(Key::char('0')), None);
+
+    let mut empty = Modal::choice("Pick", "None", vec![]);
+    assert_eq!(empty.on_key(plain(KeyCode::Enter)), None);
+}
+
+#[test]
+fn text_edits_the_input() {
+    let mut modal = Modal::text("Item", "Id?");
+    for c in "abx".chars() {
+        modal.on_key(Key::char(c));
+    }
+    modal.on_key(plain(KeyCode::Backspace));
+    modal.field_mut().unwrap().paste("c\nd");
+    assert_eq!(modal.field().map(TextInput::value), Some("abc"));
+    assert_eq!(
+        modal.on_key(plain(KeyCode::Enter)),
+        Some(ModalAnswer::Text(String::from("abc")))
+    );
+}
+
+#[test]
+fn escape_cancels_any_modal() {
+    let modals = [
+        Modal::confirm("", ""),
+        Modal::choice("", "", vec![String::from("a")]),
+        Modal::text("", ""),
+    ];
+    for mut modal in modals {
+        assert_eq!(
+            modal.on_key(plain(KeyCode::Esc)),
+            Some(ModalAnswer::Cancelled)
+        );
+        assert_eq!(modal.on_key(Key::ctrl('c')), Some(ModalAnswer::Cancelled));
+    }
+}
+
+#[test]
+fn secrets_are_masked() {
+    let mut modal = Modal::secret("Token", "Paste it");
+    modal.field_mut().unwrap().paste("hunter2");
+    let field = modal.field().unwrap();
+    assert!(field.is_masked());
+    assert_eq!(field.view(20).before, "*******");
+    assert_eq!(
+        modal.on_key(Key::plain(KeyCode::Enter)),
+        Some(ModalAnswer::Text(String::from("hunter2")))
+    );
+}
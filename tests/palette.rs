@@ -0,0 +1,65 @@
+use churro_cli::app::{
+    actions::Action,
+    palette::{fuzzy_score, Palette},
+};
+
+#[test]
+fn fuzzy_matches_subsequences_ignoring_case() {
+    assert!(fuzzy_score("", "Quit").is_some());
+    assert!(fuzzy_score("fi", "Fetch Items").is_some());
+    assert!(fuzzy_score("FETCH", "Fetch Items").is_some());
+    assert!(fuzzy_score("fx", "Fetch Items").is_none());
+    assert!(fuzzy_score("itemsf", "Fetch Items").is_none());
+}
+
+#[test]
+fn tighter_matches_score_higher() {
+    let score = |query, text| fuzzy_score(query, text).unwrap();
+    // Word starts beat letters in the middle of a word
+    assert!(score("i", "Fetch Items") > score("t", "Fetch Items"));
+    // Matches in a row beat scattered ones
+    assert!(score("inc", "Increment Delay") > score("icm", "Increment Delay"));
+    // Early matches beat late ones
+    assert!(score("de", "Decrement Delay") > score("de", "Increment Delay"));
+}
+
+fn palette(query: &str) -> Palette {
+    let mut palette = Palette::new(None);
+    palette.push_str(query);
+    palette
+}
+
+#[test]
+fn lists_every_action_but_itself() {
+    let matches = palette("").matches();
+    assert_eq!(matches.len(), Action::iterator().len() - 1);
+    assert!(!matches.contains(&Action::CommandPalette));
+    assert_eq!(matches[0], Action::Quit);
+}
+
+#[test]
+fn filters_and_ranks_as_you_type() {
+    let mut palette = palette("de");
+    assert_eq!(
+        palette.matches()[..2],
+        [Action::DecrementDelay, Action::IncrementDelay]
+    );
+    palette.push('c');
+    assert_eq!(palette.selection(), Some(Action::DecrementDelay));
+    palette.push('z');
+    assert_eq!(palette.selection(), None);
+    palette.pop();
+    assert_eq!(palette.query(), "dec");
+    let palette = self::palette("fi");
+    assert_eq!(palette.selection(), Some(Action::FetchItems));
+}
+
+#[test]
+fn selection_wraps_around() {
+    let mut palette = palette("delay");
+    assert_eq!(palette.matches().len(), 2);
+    palette.select_previous();
+    assert_eq!(palette.selected(), 1);
+    palette.select_next();
+    assert_eq!(palette.selected(), 0);
+}
@@ -0,0 +1,82 @@
+use std::{
+    fs,
+    time::{Duration, SystemTime},
+};
+
+use churro_cli::{
+    app::{jobs::JobStatus, modal::ModalAnswer, AppEvent},
+    auth::{store::TokenStore, Tokens},
+    io::{handler::IoAsyncHandler, IoEvent, IoJob, JobId},
+};
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver},
+    time::timeout,
+};
+
+/// Saved tokens where the handler looks for them, OAuth configured
+fn logged_in_env() {
+    let dir = std::env::temp_dir().join(format!("churro-cli-{}-relogin", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    std::env::set_var("XDG_DATA_HOME", &dir);
+    std::env::set_var("OAUTH_CLIENT_ID", "churro");
+    std::env::set_var("OAUTH_AUTH_URL", "http://127.0.0.1:1/authorize");
+    std::env::set_var("OAUTH_TOKEN_URL", "http://127.0.0.1:1/token");
+    TokenStore::default_location()
+        .unwrap()
+        .save(&Tokens {
+            access_token: String::from("access"),
+            refresh_token: None,
+            expires_at: Some(SystemTime::now() + Duration::from_secs(3600)),
+        })
+        .unwrap();
+}
+
+async fn send(io_tx: &mpsc::Sender<IoJob>, id: u64, event: IoEvent) {
+    io_tx
+        .send(IoJob {
+            id: JobId(id),
+            event,
+        })
+        .await
+        .unwrap();
+}
+
+async fn next_event(app_rx: &mut UnboundedReceiver<AppEvent>) -> AppEvent {
+    timeout(Duration::from_secs(5), app_rx.recv())
+        .await
+        .expect("no app event in time")
+        .expect("handler is gone")
+}
+
+#[tokio::test]
+async fn asks_before_waiting_for_a_permit() {
+    logged_in_env();
+    let (io_tx, io_rx) = mpsc::channel(100);
+    let (app_tx, mut app_rx) = mpsc::unbounded_channel();
+    tokio::spawn(IoAsyncHandler::new(app_tx).run(io_rx, 1));
+
+    send(&io_tx, 0, IoEvent::Initialize).await;
+    while !matches!(next_event(&mut app_rx).await, AppEvent::LoggedIn) {}
+
+    // The only permit is taken by the sleep until long after the answer
+    send(&io_tx, 1, IoEvent::Sleep(Duration::from_secs(30))).await;
+    send(&io_tx, 2, IoEvent::Login).await;
+    let mut asked = false;
+    loop {
+        match next_event(&mut app_rx).await {
+            AppEvent::Ask(modal, tx) => {
+                assert_eq!(modal.message(), "Already logged in, log in again?");
+                asked = true;
+                tx.send(ModalAnswer::Cancelled).unwrap();
+            }
+            AppEvent::JobStarted(JobId(2)) => panic!("declined login started"),
+            AppEvent::JobFinished(JobId(2), status) => {
+                assert_eq!(status, JobStatus::Cancelled);
+                break;
+            }
+            AppEvent::Slept => panic!("login waited for the sleep"),
+            _ => {}
+        }
+    }
+    assert!(asked);
+}
@@ -0,0 +1,133 @@
+use std::time::{Duration, SystemTime};
+
+use churro_cli::api::{
+    error::ApiError,
+    retry::{retry_after, RetryPolicy},
+};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+fn policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 4,
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(1),
+    }
+}
+
+fn server(status: u16) -> ApiError {
+    ApiError::Server {
+        status,
+        message: String::new(),
+    }
+}
+
+fn client(status: u16) -> ApiError {
+    ApiError::Client {
+        status,
+        message: String::new(),
+    }
+}
+
+#[test]
+fn retries_transient_failures_only() {
+    let policy = policy();
+    for error in [
+        ApiError::Timeout,
+        ApiError::Network(String::new()),
+        client(429),
+        server(500),
+        server(502),
+        server(503),
+        server(504),
+    ] {
+        assert!(policy.should_retry(&error, 1), "{}", error);
+    }
+    for error in [
+        client(400),
+        client(401),
+        client(404),
+        server(501),
+        ApiError::Decode(String::new()),
+        ApiError::Config(String::new()),
+        ApiError::Unauthenticated(String::new()),
+        ApiError::InvalidArgument(String::new()),
+    ] {
+        assert!(!policy.should_retry(&error, 1), "{}", error);
+    }
+}
+
+#[test]
+fn stops_after_the_last_attempt() {
+    let policy = policy();
+    assert!(policy.should_retry(&ApiError::Timeout, 3));
+    assert!(!policy.should_retry(&ApiError::Timeout, 4));
+    assert!(!RetryPolicy::none().should_retry(&ApiError::Timeout, 1));
+}
+
+#[test]
+fn delay_doubles_with_jitter_up_to_the_max() {
+    let policy = policy();
+    for _ in 0..100 {
+        for (attempt, full) in [
+            (1, 100),
+            (2, 200),
+            (3, 400),
+            (4, 800),
+            (5, 1000),
+            (30, 1000),
+        ] {
+            let full = Duration::from_millis(full);
+            let delay = policy.delay(attempt, None);
+            assert!(
+                delay >= full / 2 && delay <= full,
+                "attempt {}: {:?}",
+                attempt,
+                delay
+            );
+        }
+    }
+}
+
+#[test]
+fn delay_follows_retry_after_up_to_the_max() {
+    let policy = policy();
+    let delay = policy.delay(1, Some(Duration::from_millis(700)));
+    assert_eq!(delay, Duration::from_millis(700));
+    assert_eq!(policy.delay(1, Some(Duration::ZERO)), Duration::ZERO);
+    let delay = policy.delay(1, Some(Duration::from_secs(86400)));
+    assert_eq!(delay, policy.max_delay);
+}
+
+fn headers(value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+    headers
+}
+
+#[test]
+fn reads_retry_after_seconds() {
+    assert_eq!(retry_after(&headers("120")), Some(Duration::from_secs(120)));
+    assert_eq!(retry_after(&headers(" 3 ")), Some(Duration::from_secs(3)));
+    assert_eq!(retry_after(&HeaderMap::new()), None);
+}
+
+#[test]
+fn reads_retry_after_dates() {
+    let later = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(100));
+    let delay = retry_after(&headers(&later)).unwrap();
+    // HTTP dates are to the second
+    assert!(
+        delay > Duration::from_secs(98) && delay <= Duration::from_secs(100),
+        "{:?}",
+        delay
+    );
+    let past = httpdate::fmt_http_date(SystemTime::now() - Duration::from_secs(100));
+    assert_eq!(retry_after(&headers(&past)), Some(Duration::ZERO));
+}
+
+#[test]
+fn ignores_garbage_retry_after() {
+    for value in ["soon", "-1", "1.5", "Tue, 99 Foo 2024 00:00:00 GMT", ""] {
+        assert_eq!(retry_after(&headers(value)), None, "{:?}", value);
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fs,
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+use churro_cli::auth::{
+    session::{Session, REFRESH_MARGIN},
+    store::TokenStore,
+    OAuthConfig, Tokens,
+};
+use hyper::{
+    body,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+
+/// Stand-in token endpoint, answering refresh requests without
+/// rotating the refresh token, or rejecting them all
+async fn token_endpoint(req: Request<Body>, reject: bool) -> Result<Response<Body>, Infallible> {
+    let form = body::to_bytes(req.into_body()).await.unwrap();
+    let form: HashMap<String, String> = url::form_urlencoded::parse(&form).into_owned().collect();
+    assert_eq!(form["grant_type"], "refresh_token");
+    assert_eq!(form["refresh_token"], "refresh");
+    let response = if reject {
+        Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"error":"invalid_grant"}"#))
+    } else {
+        Response::builder()
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"access_token":"fresh","token_type":"bearer","expires_in":3600}"#,
+            ))
+    };
+    Ok(response.unwrap())
+}
+
+fn start_provider(reject: bool) -> SocketAddr {
+    let make_svc = make_service_fn(move |_| async move {
+        Ok::<_, Infallible>(service_fn(move |req| token_endpoint(req, reject)))
+    });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+fn config(provider: SocketAddr) -> OAuthConfig {
+    OAuthConfig {
+        client_id: String::from("churro"),
+        client_secret: None,
+        auth_url: format!("http://{}/authorize", provider),
+        token_url: format!("http://{}/token", provider),
+        scopes: vec![],
+        redirect_port: 0,
+        callback_timeout: Duration::from_secs(5),
+    }
+}
+
+fn store(name: &str) -> TokenStore {
+    let dir = std::env::temp_dir().join(format!("churro-cli-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    TokenStore::new(dir.join("tokens.json"))
+}
+
+fn remove(store: &TokenStore) {
+    fs::remove_dir_all(store.path().parent().unwrap()).unwrap();
+}
+
+/// Logged in with tokens expiring in `expires_in`, saved in the store
+fn session(provider: SocketAddr, store: &TokenStore, expires_in: Duration) -> Session {
+    let mut session = Session::new(config(provider), store.clone());
+    session
+        .set_tokens(Tokens {
+            access_token: String::from("access"),
+            refresh_token: Some(String::from("refresh")),
+            expires_at: Some(SystemTime::now() + expires_in),
+        })
+        .unwrap();
+    session
+}
+
+#[tokio::test]
+async fn keeps_tokens_until_the_refresh_margin() {
+    // Rejecting, a refresh would log out
+    let provider = start_provider(true);
+    let store = store("outside-margin");
+    let mut session = session(provider, &store, REFRESH_MARGIN * 2);
+
+    assert_eq!(session.access_token().await.unwrap(), "access");
+    assert!(session.is_logged_in());
+    remove(&store);
+}
+
+#[tokio::test]
+async fn refreshes_inside_the_refresh_margin() {
+    let provider = start_provider(false);
+    let store = store("inside-margin");
+    let mut session = session(provider, &store, REFRESH_MARGIN / 2);
+
+    assert_eq!(session.access_token().await.unwrap(), "fresh");
+    // Saved, the refresh token kept as it wasn't rotated
+    let saved = store.load().unwrap().unwrap();
+    assert_eq!(saved.access_token, "fresh");
+    assert_eq!(saved.refresh_token.as_deref(), Some("refresh"));
+    assert!(saved.expires_at.unwrap() > SystemTime::now() + REFRESH_MARGIN);
+
+    // Not refreshed again
+    assert_eq!(session.access_token().await.unwrap(), "fresh");
+    remove(&store);
+}
+
+#[tokio::test]
+async fn logs_out_when_the_refresh_fails() {
+    let provider = start_provider(true);
+    let store = store("refresh-fails");
+    let mut session = session(provider, &store, Duration::ZERO);
+
+    let err = session.access_token().await.unwrap_err();
+    assert!(err.to_string().contains("refresh failed"), "{}", err);
+    assert!(!session.is_logged_in());
+    assert!(store.load().unwrap().is_none());
+    assert!(session.access_token().await.is_err());
+    remove(&store);
+}
+
+#[tokio::test]
+async fn restores_the_saved_tokens() {
+    let provider = start_provider(true);
+    let store = store("restore");
+    let mut empty = Session::new(config(provider), store.clone());
+    assert!(empty.restore().await.is_err());
+    assert!(!empty.is_logged_in());
+
+    session(provider, &store, REFRESH_MARGIN * 2);
+    let mut restored = Session::new(config(provider), store.clone());
+    restored.restore().await.unwrap();
+    assert!(restored.is_logged_in());
+    assert_eq!(restored.access_token().await.unwrap(), "access");
+    remove(&store);
+}
+
+#[tokio::test]
+async fn keeps_the_tokens_when_the_provider_is_unreachable() {
+    // Nothing listens there once the listener is dropped
+    let unreachable = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let store = store("unreachable");
+    let mut session = session(unreachable, &store, Duration::ZERO);
+
+    let err = session.access_token().await.unwrap_err();
+    assert!(err.to_string().contains("Could not refresh"), "{}", err);
+    assert!(session.is_logged_in());
+    let saved = store.load().unwrap().unwrap();
+    assert_eq!(saved.refresh_token.as_deref(), Some("refresh"));
+
+    // Tried again on the next call, and restored with it
+    assert!(session.access_token().await.is_err());
+    assert!(session.is_logged_in());
+    let mut restored = Session::new(config(unreachable), store.clone());
+    assert!(restored.restore().await.is_err());
+    assert!(restored.is_logged_in());
+    remove(&store);
+}
@@ -0,0 +1,41 @@
+use churro_cli::terminal::Suspension;
+
+#[test]
+fn own_suspend_is_resumed_once() {
+    let mut suspension = Suspension::default();
+    // Stopped and continued by someone else
+    assert!(suspension.resumed());
+
+    suspension.suspended();
+    assert_eq!(suspension.resumed(), cfg!(not(unix)));
+    assert!(suspension.resumed());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn signals_become_input_events() {
+    use std::time::Duration;
+
+    use churro_cli::inputs::{events::Signals, InputEvent};
+
+    // One test for all of them, signals go to the whole process
+    let mut signals = Signals::new();
+    for (signal, resumed) in [
+        (libc::SIGCONT, true),
+        (libc::SIGTERM, false),
+        (libc::SIGHUP, false),
+    ] {
+        // SAFETY: raising a signal has no memory safety implication
+        unsafe {
+            libc::raise(signal);
+        }
+        let event = tokio::time::timeout(Duration::from_secs(5), signals.recv())
+            .await
+            .expect("signal not received");
+        if resumed {
+            assert!(matches!(event, InputEvent::Resumed), "{:?}", event);
+        } else {
+            assert!(matches!(event, InputEvent::Terminate), "{:?}", event);
+        }
+    }
+}
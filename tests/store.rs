@@ -0,0 +1,81 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use churro_cli::auth::{store::TokenStore, Tokens};
+
+/// Empty dir of its own for each test
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("churro-cli-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn tokens() -> Tokens {
+    Tokens {
+        access_token: String::from("access"),
+        refresh_token: Some(String::from("refresh")),
+        expires_at: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+    }
+}
+
+#[test]
+fn saves_and_loads_tokens() {
+    let dir = temp_dir("round-trip");
+    let store = TokenStore::new(dir.join("nested").join("tokens.json"));
+    assert!(store.load().unwrap().is_none());
+
+    store.save(&tokens()).unwrap();
+    let loaded = store.load().unwrap().unwrap();
+    assert_eq!(loaded.access_token, "access");
+    assert_eq!(loaded.refresh_token.as_deref(), Some("refresh"));
+    assert_eq!(loaded.expires_at, tokens().expires_at);
+
+    // Only the tokens file is left behind the rename
+    let files = fs::read_dir(dir.join("nested"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<Vec<_>>();
+    assert_eq!(files, ["tokens.json"]);
+
+    store.clear().unwrap();
+    assert!(store.load().unwrap().is_none());
+    // Clearing twice is fine
+    store.clear().unwrap();
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn tokens_file_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_dir("private");
+    let store = TokenStore::new(dir.join("tokens.json"));
+    // Permissions are fixed even for a file that was readable before
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("tokens.json.tmp"), "").unwrap();
+    fs::set_permissions(
+        dir.join("tokens.json.tmp"),
+        fs::Permissions::from_mode(0o644),
+    )
+    .unwrap();
+
+    store.save(&tokens()).unwrap();
+    let mode = fs::metadata(store.path()).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert!(!dir.join("tokens.json.tmp").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn corrupted_file_is_an_error() {
+    let dir = temp_dir("corrupted");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("tokens.json"), "not json").unwrap();
+    let err = TokenStore::new(dir.join("tokens.json")).load().unwrap_err();
+    assert!(err.to_string().contains("Corrupted"));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,211 @@
+use churro_cli::{
+    app::{
+        table::{Sort, TableRow, TableView, MIN_COLUMN_WIDTH},
+        ui::draw_table,
+    },
+    inputs::key::{Key, KeyCode},
+};
+use tui::{buffer::Buffer, layout::Rect};
+
+struct Row(&'static str, &'static str);
+
+impl TableRow for Row {
+    fn key(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![self.0.to_string(), self.1.to_string()]
+    }
+}
+
+fn rows() -> Vec<Row> {
+    vec![
+        Row("10", "banana"),
+        Row("2", "Cherry"),
+        Row("1", "apple"),
+        Row("30", "date"),
+    ]
+}
+
+fn table() -> TableView {
+    TableView::new(&[("ID", 4), ("Name", 10)])
+}
+
+fn press(table: &mut TableView, rows: &[Row], keys: &[Key]) {
+    for key in keys {
+        assert!(table.on_key(*key, rows, 2), "{} not handled", key);
+    }
+}
+
+fn selected(table: &TableView, rows: &[Row]) -> &'static str {
+    table.selection(rows).unwrap().0
+}
+
+#[test]
+fn moves_within_the_rows() {
+    let rows = rows();
+    let mut table = table();
+    press(&mut table, &rows, &[Key::char('k')]);
+    assert_eq!(selected(&table, &rows), "10");
+    press(
+        &mut table,
+        &rows,
+        &[Key::char('j'), Key::plain(KeyCode::Down)],
+    );
+    assert_eq!(selected(&table, &rows), "1");
+    press(
+        &mut table,
+        &rows,
+        &[Key::plain(KeyCode::End), Key::char('j')],
+    );
+    assert_eq!(selected(&table, &rows), "30");
+    press(&mut table, &rows, &[Key::plain(KeyCode::Up)]);
+    assert_eq!(selected(&table, &rows), "1");
+    press(&mut table, &rows, &[Key::plain(KeyCode::Home)]);
+    assert_eq!(selected(&table, &rows), "10");
+    assert!(!table.on_key(Key::char('q'), &rows, 2));
+    assert!(!table.clone().read_only().on_key(Key::char('j'), &rows, 2));
+    assert!(TableView::new(&[]).selection::<Row>(&[]).is_none());
+}
+
+/// Rows shown and the IDs on them, in a table fitting two rows
+fn draw(table: &TableView, top: &mut usize, rows: &[Row]) -> (usize, Vec<String>) {
+    let area = Rect::new(0, 0, 20, 5);
+    let mut buf = Buffer::empty(area);
+    let shown = draw_table(table, top, rows, String::from("Rows"), area, &mut buf);
+    // Below the border and the header, after the mark column
+    let ids = (2..4)
+        .map(|y| {
+            let id = (3..7)
+                .map(|x| buf.get(x, y).symbol.as_str())
+                .collect::<String>();
+            id.trim().to_string()
+        })
+        .collect();
+    (shown, ids)
+}
+
+#[test]
+fn pages_by_the_rows_shown() {
+    let rows = rows();
+    let mut table = table();
+    let mut top = 0;
+    assert_eq!(
+        draw(&table, &mut top, &rows),
+        (2, vec!["10".into(), "2".into()])
+    );
+    press(&mut table, &rows, &[Key::plain(KeyCode::PageDown)]);
+    assert_eq!(selected(&table, &rows), "1");
+    // Scrolled just enough to show the selection
+    assert_eq!(draw(&table, &mut top, &rows).1, ["2", "1"]);
+    assert_eq!(top, 1);
+    press(&mut table, &rows, &[Key::plain(KeyCode::PageDown)]);
+    assert_eq!(selected(&table, &rows), "30");
+    assert_eq!(draw(&table, &mut top, &rows).1, ["1", "30"]);
+    press(&mut table, &rows, &[Key::plain(KeyCode::PageUp)]);
+    assert_eq!(draw(&table, &mut top, &rows).1, ["2", "1"]);
+    press(&mut table, &rows, &[Key::plain(KeyCode::Home)]);
+    assert_eq!(draw(&table, &mut top, &rows).1, ["10", "2"]);
+    // Rows gone since don't leave it scrolled past the end
+    assert_eq!(draw(&table, &mut top, &rows[..1]).1, ["10", ""]);
+}
+
+#[test]
+fn marks_with_space() {
+    let rows = rows();
+    let mut table = table();
+    let space = Key::char(' ');
+    press(
+        &mut table,
+        &rows,
+        &[space, Key::char('j'), Key::char('j'), space],
+    );
+    assert_eq!(
+        table
+            .marked(&rows)
+            .iter()
+            .map(|row| row.0)
+            .collect::<Vec<_>>(),
+        ["10", "1"]
+    );
+    press(&mut table, &rows, &[space]);
+    assert!(!table.is_marked(&rows[2]));
+
+    // Marks follow the rows when they're reloaded
+    let reloaded = vec![Row("1", "apple"), Row("10", "banana")];
+    assert_eq!(table.marked(&reloaded).len(), 1);
+    assert!(table.is_marked(&reloaded[1]));
+}
+
+#[test]
+fn sorts_by_the_current_column() {
+    let rows = rows();
+    let mut table = table();
+    press(&mut table, &rows, &[Key::char('o')]);
+    assert_eq!(
+        table.sort(),
+        Some(Sort {
+            column: 0,
+            descending: false
+        })
+    );
+    // By value when they're numbers, the selection follows its row
+    assert_eq!(table.order(&rows), [2, 1, 0, 3]);
+    assert_eq!(selected(&table, &rows), "10");
+    assert_eq!(table.selected(), 2);
+    press(&mut table, &rows, &[Key::char('o')]);
+    assert_eq!(table.order(&rows), [3, 0, 1, 2]);
+
+    // Ignoring case otherwise
+    press(
+        &mut table,
+        &rows,
+        &[Key::char('l'), Key::char('l'), Key::char('o')],
+    );
+    assert_eq!(table.column(), 1);
+    assert_eq!(table.order(&rows), [2, 0, 1, 3]);
+    press(&mut table, &rows, &[Key::plain(KeyCode::Left)]);
+    assert_eq!(table.column(), 0);
+}
+
+#[test]
+fn resizes_the_current_column() {
+    let rows = rows();
+    let mut table = table();
+    press(&mut table, &rows, &[Key::char('>'), Key::char('>')]);
+    assert_eq!(table.columns()[0].width, 6);
+    for _ in 0..10 {
+        press(&mut table, &rows, &[Key::char('<')]);
+    }
+    assert_eq!(table.columns()[0].width, MIN_COLUMN_WIDTH);
+    assert_eq!(table.columns()[1].width, 10);
+}
+
+#[test]
+fn read_only_tables_draw_from_their_position() {
+    let rows = rows();
+    let mut table = table().read_only();
+    table.select(rows.len(), 1);
+    let area = Rect::new(0, 0, 20, 5);
+    let mut buf = Buffer::empty(area);
+    let mut top = 0;
+    draw_table(&table, &mut top, &rows, String::new(), area, &mut buf);
+    assert_eq!(top, 1);
+    // No marks column, the IDs start right after the border
+    let ids = (2..4)
+        .map(|y| {
+            let id = (1..5)
+                .map(|x| buf.get(x, y).symbol.as_str())
+                .collect::<String>();
+            id.trim().to_string()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["2", "1"]);
+
+    // No further than the last rows
+    table.select(rows.len(), 3);
+    draw_table(&table, &mut top, &rows, String::new(), area, &mut buf);
+    assert_eq!(buf.get(1, 3).symbol, "3");
+    assert_eq!(top, 2);
+}